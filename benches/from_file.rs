@@ -0,0 +1,54 @@
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use moviehash::{CHUNK_SIZE, MovieHash};
+
+const MOVIE: &str = "test-files/breakdance.avi";
+
+/// The original implementation, which reads the head and tail one 8 byte
+/// word at a time through a `BufReader` and seeks in between.
+fn read_exact_words(file: &File) -> u64 {
+    let size = file.metadata().unwrap().len();
+    let mut hash = size;
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE as usize, file);
+    let mut word = [0u8; 8];
+
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    for _ in 0..CHUNK_SIZE / 8 {
+        reader.read_exact(&mut word).unwrap();
+        hash = hash.wrapping_add(u64::from_le_bytes(word));
+    }
+
+    reader.seek(SeekFrom::Start(size - CHUNK_SIZE)).unwrap();
+    for _ in 0..CHUNK_SIZE / 8 {
+        reader.read_exact(&mut word).unwrap();
+        hash = hash.wrapping_add(u64::from_le_bytes(word));
+    }
+
+    hash
+}
+
+fn from_file(c: &mut Criterion) {
+    let file = File::open(MOVIE).unwrap();
+    assert_eq!(
+        read_exact_words(&file),
+        MovieHash::from_file(&file).unwrap().0
+    );
+
+    let mut group = c.benchmark_group("from_file");
+    group.bench_function("read_exact_words", |b| {
+        b.iter(|| read_exact_words(black_box(&file)))
+    });
+    group.bench_function("seek_and_read", |b| {
+        b.iter(|| MovieHash::from_reader(black_box(&file)).unwrap())
+    });
+    group.bench_function("read_at", |b| {
+        b.iter(|| MovieHash::from_file(black_box(&file)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, from_file);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+#ifndef MOVIEHASH_H
+#define MOVIEHASH_H
+
+/* Generated by cbindgen from capi/src/lib.rs, do not edit. */
+
+#include <stdint.h>
+#include <stddef.h>
+
+#if defined(__unix__) || defined(__APPLE__)
+#define MOVIEHASH_UNIX
+#endif
+
+/**
+ * Outcome of every `moviehash_*` function. Anything but `Ok` leaves the
+ * output hash untouched.
+ */
+typedef enum MoviehashStatus {
+  MOVIEHASH_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL.
+   */
+  MOVIEHASH_STATUS_NULL_POINTER = 1,
+  /**
+   * The path cannot be represented on this platform.
+   */
+  MOVIEHASH_STATUS_INVALID_PATH = 2,
+  /**
+   * The file is smaller than 64 KiB.
+   */
+  MOVIEHASH_STATUS_SMALL_SIZE = 3,
+  /**
+   * A head or tail buffer does not hold exactly 64 KiB.
+   */
+  MOVIEHASH_STATUS_INVALID_BUFFER = 4,
+  MOVIEHASH_STATUS_NOT_FOUND = 5,
+  MOVIEHASH_STATUS_PERMISSION_DENIED = 6,
+  /**
+   * Any other I/O error.
+   */
+  MOVIEHASH_STATUS_IO = 7,
+} MoviehashStatus;
+
+/**
+ * Computes the hash of the file at the NUL-terminated `path` and stores it
+ * in `hash`.
+ *
+ * # Safety
+ *
+ * `path` must be NULL or point to a NUL-terminated string, and `hash` must
+ * be NULL or valid for writes.
+ */
+enum MoviehashStatus moviehash_from_path(const char *path, uint64_t *hash);
+
+#if defined(MOVIEHASH_UNIX)
+/**
+ * Computes the hash of the open file descriptor `fd` and stores it in
+ * `hash`. The descriptor is neither closed nor moved, since the file is read
+ * with positional reads. Only available on POSIX systems.
+ *
+ * # Safety
+ *
+ * `fd` must be an open file descriptor for the duration of the call, and
+ * `hash` must be NULL or valid for writes.
+ */
+enum MoviehashStatus moviehash_from_fd(int fd, uint64_t *hash);
+#endif
+
+/**
+ * Computes the hash of a file of `size` bytes from its first and last
+ * 64 KiB, for players that already read them, and stores it in `hash`.
+ *
+ * # Safety
+ *
+ * `head` and `tail` must be NULL or valid for reads of `head_len` and
+ * `tail_len` bytes, and `hash` must be NULL or valid for writes.
+ */
+enum MoviehashStatus moviehash_from_buffers(uint64_t size,
+                                            const uint8_t *head,
+                                            size_t head_len,
+                                            const uint8_t *tail,
+                                            size_t tail_len,
+                                            uint64_t *hash);
+
+/**
+ * Returns a static, NUL-terminated description of `status`, one of the
+ * `MoviehashStatus` values. Unknown values are described as such.
+ */
+const char *moviehash_status_message(int status);
+
+#endif  /* MOVIEHASH_H */
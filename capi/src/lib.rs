@@ -0,0 +1,262 @@
+use std::ffi::{CStr, c_char, c_int};
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+
+use moviehash::{Error, MovieHash, PartsError};
+
+/// Outcome of every `moviehash_*` function. Anything but `Ok` leaves the
+/// output hash untouched.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoviehashStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL.
+    NullPointer = 1,
+    /// The path cannot be represented on this platform.
+    InvalidPath = 2,
+    /// The file is smaller than 64 KiB.
+    SmallSize = 3,
+    /// A head or tail buffer does not hold exactly 64 KiB.
+    InvalidBuffer = 4,
+    NotFound = 5,
+    PermissionDenied = 6,
+    /// Any other I/O error.
+    Io = 7,
+}
+
+impl From<&Error> for MoviehashStatus {
+    fn from(value: &Error) -> Self {
+        match value {
+            Error::SmallSize { .. } => Self::SmallSize,
+            Error::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => Self::NotFound,
+                io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+                _ => Self::Io,
+            },
+        }
+    }
+}
+
+/// Stores the outcome of a computation in `hash`.
+fn finish(result: Result<MovieHash, Error>, hash: *mut u64) -> MoviehashStatus {
+    match result {
+        Ok(value) => {
+            // SAFETY: every caller has checked that `hash` is not NULL.
+            unsafe { hash.write(value.0) };
+            MoviehashStatus::Ok
+        }
+        Err(err) => MoviehashStatus::from(&err),
+    }
+}
+
+/// Computes the hash of the file at the NUL-terminated `path` and stores it
+/// in `hash`.
+///
+/// # Safety
+///
+/// `path` must be NULL or point to a NUL-terminated string, and `hash` must
+/// be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn moviehash_from_path(
+    path: *const c_char,
+    hash: *mut u64,
+) -> MoviehashStatus {
+    if path.is_null() || hash.is_null() {
+        return MoviehashStatus::NullPointer;
+    }
+
+    // SAFETY: checked for NULL above, the caller guarantees the terminator.
+    let Some(path) = path_from_c(unsafe { CStr::from_ptr(path) }) else {
+        return MoviehashStatus::InvalidPath;
+    };
+
+    finish(MovieHash::from_path(path), hash)
+}
+
+#[cfg(unix)]
+fn path_from_c(path: &CStr) -> Option<PathBuf> {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    Some(PathBuf::from(OsStr::from_bytes(path.to_bytes())))
+}
+
+#[cfg(not(unix))]
+fn path_from_c(path: &CStr) -> Option<PathBuf> {
+    path.to_str().ok().map(PathBuf::from)
+}
+
+/// Computes the hash of the open file descriptor `fd` and stores it in
+/// `hash`. The descriptor is neither closed nor moved, since the file is read
+/// with positional reads. Only available on POSIX systems.
+///
+/// # Safety
+///
+/// `fd` must be an open file descriptor for the duration of the call, and
+/// `hash` must be NULL or valid for writes.
+#[cfg(unix)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn moviehash_from_fd(fd: c_int, hash: *mut u64) -> MoviehashStatus {
+    use std::mem::ManuallyDrop;
+    use std::os::fd::FromRawFd;
+
+    if hash.is_null() {
+        return MoviehashStatus::NullPointer;
+    }
+
+    // SAFETY: the caller guarantees `fd` is open, and `ManuallyDrop` keeps
+    // it from being closed when `file` goes out of scope.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+
+    finish(MovieHash::from_file(&file), hash)
+}
+
+/// Computes the hash of a file of `size` bytes from its first and last
+/// 64 KiB, for players that already read them, and stores it in `hash`.
+///
+/// # Safety
+///
+/// `head` and `tail` must be NULL or valid for reads of `head_len` and
+/// `tail_len` bytes, and `hash` must be NULL or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn moviehash_from_buffers(
+    size: u64,
+    head: *const u8,
+    head_len: usize,
+    tail: *const u8,
+    tail_len: usize,
+    hash: *mut u64,
+) -> MoviehashStatus {
+    if head.is_null() || tail.is_null() || hash.is_null() {
+        return MoviehashStatus::NullPointer;
+    }
+
+    // SAFETY: checked for NULL above, the caller guarantees the lengths.
+    let (head, tail) = unsafe {
+        (
+            std::slice::from_raw_parts(head, head_len),
+            std::slice::from_raw_parts(tail, tail_len),
+        )
+    };
+
+    match MovieHash::from_parts(size, head, tail) {
+        Err(PartsError::Length { .. }) => MoviehashStatus::InvalidBuffer,
+        result => finish(result.map_err(Error::from), hash),
+    }
+}
+
+/// Returns a static, NUL-terminated description of `status`, one of the
+/// `MoviehashStatus` values. Unknown values are described as such.
+#[unsafe(no_mangle)]
+pub extern "C" fn moviehash_status_message(status: c_int) -> *const c_char {
+    // Taken as an int since C callers can pass values outside the enum.
+    let message = match status {
+        0 => c"success",
+        1 => c"a required argument is NULL",
+        2 => c"path is not valid on this platform",
+        3 => c"file is smaller than 64 KiB",
+        4 => c"head and tail must be 64 KiB each",
+        5 => c"file not found",
+        6 => c"permission denied",
+        7 => c"I/O error",
+        _ => c"unknown status",
+    };
+
+    message.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    const MOVIE: &CStr = c"../test-files/breakdance.avi";
+
+    #[test]
+    fn should_hash_path_and_buffers() {
+        let mut hash = 0;
+
+        assert_eq!(
+            unsafe { moviehash_from_path(MOVIE.as_ptr(), &mut hash) },
+            MoviehashStatus::Ok
+        );
+        assert_eq!(hash, 0x8e245d9679d31e12);
+
+        let bytes = std::fs::read("../test-files/breakdance.avi").unwrap();
+        let tail = &bytes[bytes.len() - 65536..];
+        hash = 0;
+
+        assert_eq!(
+            unsafe {
+                moviehash_from_buffers(
+                    bytes.len() as u64,
+                    bytes.as_ptr(),
+                    65536,
+                    tail.as_ptr(),
+                    tail.len(),
+                    &mut hash,
+                )
+            },
+            MoviehashStatus::Ok
+        );
+        assert_eq!(hash, 0x8e245d9679d31e12);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_hash_fd_without_closing_it() {
+        use std::os::fd::AsRawFd;
+
+        let file = File::open("../test-files/breakdance.avi").unwrap();
+        let mut hash = 0;
+
+        for _ in 0..2 {
+            assert_eq!(
+                unsafe { moviehash_from_fd(file.as_raw_fd(), &mut hash) },
+                MoviehashStatus::Ok
+            );
+            assert_eq!(hash, 0x8e245d9679d31e12);
+        }
+    }
+
+    #[test]
+    fn should_map_errors_to_status() {
+        let mut hash = 42;
+        let status =
+            |path: &CStr, hash: &mut u64| unsafe { moviehash_from_path(path.as_ptr(), hash) };
+
+        assert_eq!(
+            status(c"../test-files/small.txt", &mut hash),
+            MoviehashStatus::SmallSize
+        );
+        assert_eq!(
+            status(c"../test-files/non-existing.avi", &mut hash),
+            MoviehashStatus::NotFound
+        );
+        assert_eq!(
+            unsafe { moviehash_from_path(ptr::null(), &mut hash) },
+            MoviehashStatus::NullPointer
+        );
+        assert_eq!(
+            unsafe {
+                moviehash_from_buffers(70000, [0; 8].as_ptr(), 8, [0; 8].as_ptr(), 8, &mut hash)
+            },
+            MoviehashStatus::InvalidBuffer
+        );
+        assert_eq!(hash, 42);
+        assert_eq!(
+            unsafe {
+                CStr::from_ptr(moviehash_status_message(
+                    MoviehashStatus::SmallSize as c_int,
+                ))
+            },
+            c"file is smaller than 64 KiB"
+        );
+        assert_eq!(
+            unsafe { CStr::from_ptr(moviehash_status_message(-1)) },
+            c"unknown status"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+
+/// Compiles `tests/c/test.c` against the generated header and the static
+/// library, then runs it.
+#[test]
+fn c_program_should_link_and_pass() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let deps = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let library = deps.join("libmoviehash_capi.a");
+    let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("moviehash-c-test");
+
+    let status = Command::new(std::env::var_os("CC").unwrap_or("cc".into()))
+        .arg("-std=c99")
+        .arg("-D_POSIX_C_SOURCE=200809L")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(crate_dir.join("include"))
+        .arg(crate_dir.join("tests/c/test.c"))
+        .arg(&library)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .expect("a C compiler is required to run this test");
+    assert!(status.success(), "failed to compile tests/c/test.c");
+
+    let output = Command::new(&program)
+        .arg(crate_dir.join("../test-files/breakdance.avi"))
+        .arg(crate_dir.join("../test-files/small.txt"))
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"8e245d9679d31e12\n");
+}
@@ -0,0 +1,69 @@
+/* Exercises the C API through the generated header. Run by tests/c.rs. */
+
+#include <fcntl.h>
+#include <inttypes.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#include "moviehash.h"
+
+#define CHUNK_SIZE 65536
+#define EXPECTED UINT64_C(0x8e245d9679d31e12)
+
+#define CHECK(condition)                                                  \
+    do {                                                                  \
+        if (!(condition)) {                                               \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,        \
+                    __LINE__, #condition);                                \
+            exit(1);                                                      \
+        }                                                                 \
+    } while (0)
+
+int main(int argc, char **argv) {
+    static uint8_t head[CHUNK_SIZE], tail[CHUNK_SIZE];
+    uint64_t hash = 0;
+    off_t size;
+    int fd;
+
+    if (argc != 3) {
+        fprintf(stderr, "usage: %s MOVIE SMALL_FILE\n", argv[0]);
+        return 2;
+    }
+
+    CHECK(moviehash_from_path(argv[1], &hash) == MOVIEHASH_STATUS_OK);
+    CHECK(hash == EXPECTED);
+
+    fd = open(argv[1], O_RDONLY);
+    CHECK(fd >= 0);
+    CHECK(lseek(fd, 1000, SEEK_SET) == 1000);
+    hash = 0;
+    CHECK(moviehash_from_fd(fd, &hash) == MOVIEHASH_STATUS_OK);
+    CHECK(hash == EXPECTED);
+    CHECK(lseek(fd, 0, SEEK_CUR) == 1000);
+
+    size = lseek(fd, 0, SEEK_END);
+    CHECK(pread(fd, head, CHUNK_SIZE, 0) == CHUNK_SIZE);
+    CHECK(pread(fd, tail, CHUNK_SIZE, size - CHUNK_SIZE) == CHUNK_SIZE);
+    CHECK(close(fd) == 0);
+    hash = 0;
+    CHECK(moviehash_from_buffers((uint64_t)size, head, sizeof head, tail,
+                                 sizeof tail, &hash) == MOVIEHASH_STATUS_OK);
+    CHECK(hash == EXPECTED);
+
+    CHECK(moviehash_from_path(argv[2], &hash) == MOVIEHASH_STATUS_SMALL_SIZE);
+    CHECK(moviehash_from_path("non-existing.avi", &hash) ==
+          MOVIEHASH_STATUS_NOT_FOUND);
+    CHECK(moviehash_from_path(NULL, &hash) == MOVIEHASH_STATUS_NULL_POINTER);
+    CHECK(moviehash_from_buffers((uint64_t)size, head, 8, tail, sizeof tail,
+                                 &hash) == MOVIEHASH_STATUS_INVALID_BUFFER);
+    CHECK(hash == EXPECTED);
+    CHECK(strcmp(moviehash_status_message(MOVIEHASH_STATUS_SMALL_SIZE),
+                 "file is smaller than 64 KiB") == 0);
+    CHECK(strcmp(moviehash_status_message(99), "unknown status") == 0);
+
+    printf("%016" PRIx64 "\n", hash);
+
+    return 0;
+}
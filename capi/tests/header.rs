@@ -0,0 +1,28 @@
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn header_should_be_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+
+    let header = crate_dir.join("include/moviehash.h");
+
+    if std::env::var_os("MOVIEHASH_BLESS").is_some() {
+        fs::write(&header, &generated).unwrap();
+    }
+
+    assert_eq!(
+        String::from_utf8(generated).unwrap(),
+        fs::read_to_string(header).unwrap(),
+        "include/moviehash.h is stale, rerun with MOVIEHASH_BLESS=1"
+    );
+}
@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+
+use moviehash::{Error, MovieHash, PartsError};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+
+create_exception!(
+    moviehash,
+    MovieHashError,
+    PyException,
+    "Base class of the errors raised by this module."
+);
+create_exception!(
+    moviehash,
+    SmallSizeError,
+    MovieHashError,
+    "The file is too small to hash. Its args are `(message, path, size, min)`."
+);
+create_exception!(
+    moviehash,
+    IoError,
+    MovieHashError,
+    "The file could not be read. Its args are `(message, path, errno)`."
+);
+
+/// Raises the exception matching each [`Error`] variant.
+fn to_py_err(err: Error) -> PyErr {
+    let message = err.to_string();
+
+    match err {
+        Error::SmallSize { path, size, min } => SmallSizeError::new_err((message, path, size, min)),
+        Error::Io { path, source } => IoError::new_err((message, path, source.raw_os_error())),
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "moviehash")]
+mod moviehash_python {
+    use super::*;
+
+    #[pymodule_export]
+    use super::{IoError, MovieHashError, SmallSizeError};
+
+    /// Returns the hash of the file at `path` as 16 hex digits.
+    #[pyfunction]
+    fn from_path(py: Python<'_>, path: PathBuf) -> PyResult<String> {
+        py.detach(|| MovieHash::from_path(&path))
+            .map(|hash| hash.as_hex())
+            .map_err(to_py_err)
+    }
+
+    /// Returns the hash of a file of `size` bytes from its first and last
+    /// 64 KiB as 16 hex digits. Buffers of another length raise `ValueError`.
+    #[pyfunction]
+    fn from_bytes(size: u64, head: &[u8], tail: &[u8]) -> PyResult<String> {
+        match MovieHash::from_parts(size, head, tail) {
+            Ok(hash) => Ok(hash.as_hex()),
+            // Buffers of the wrong length are the caller's mistake.
+            Err(err @ PartsError::Length { .. }) => Err(PyValueError::new_err(err.to_string())),
+            Err(err) => Err(to_py_err(err.into())),
+        }
+    }
+
+    /// Hashes every file in `paths` without holding the GIL, returning for
+    /// each either its hash or the exception it would have raised.
+    #[pyfunction]
+    fn from_paths(py: Python<'_>, paths: Vec<PathBuf>) -> PyResult<Vec<Py<PyAny>>> {
+        let results: Vec<_> = py.detach(|| paths.iter().map(MovieHash::from_path).collect());
+
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(hash) => Ok(hash.as_hex().into_pyobject(py)?.into_any().unbind()),
+                Err(err) => Ok(to_py_err(err).into_value(py).into_any()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    /// Runs `code` with the module bound to `moviehash` and the test files
+    /// directory to `files`.
+    fn run(code: &str) {
+        Python::attach(|py| {
+            let globals = PyDict::new(py);
+            globals
+                .set_item("moviehash", pyo3::wrap_pymodule!(moviehash_python)(py))
+                .unwrap();
+            globals
+                .set_item(
+                    "files",
+                    concat!(env!("CARGO_MANIFEST_DIR"), "/../test-files"),
+                )
+                .unwrap();
+
+            py.run(&std::ffi::CString::new(code).unwrap(), Some(&globals), None)
+                .map_err(|err| err.display(py))
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn should_return_hex_hashes() {
+        run(r#"
+movie = files + "/breakdance.avi"
+assert moviehash.from_path(movie) == "8e245d9679d31e12"
+
+with open(movie, "rb") as f:
+    data = f.read()
+assert moviehash.from_bytes(len(data), data[:65536], data[-65536:]) == "8e245d9679d31e12"
+"#);
+    }
+
+    #[test]
+    fn should_raise_exception_for_each_error_variant() {
+        run(r#"
+try:
+    moviehash.from_path(files + "/small.txt")
+    assert False
+except moviehash.SmallSizeError as e:
+    assert isinstance(e, moviehash.MovieHashError)
+    assert e.args[2:] == (20, 65536)
+    assert str(e.args[1]).endswith("small.txt")
+
+try:
+    moviehash.from_path(files + "/non-existing.avi")
+    assert False
+except moviehash.IoError as e:
+    assert e.args[2] == 2
+
+try:
+    moviehash.from_bytes(70000, b"", b"")
+    assert False
+except ValueError as e:
+    assert not isinstance(e, moviehash.MovieHashError)
+    assert e.args[0] == "expected 65536 bytes of file data, got 0"
+"#);
+    }
+
+    #[test]
+    fn should_hash_batch_without_raising() {
+        run(r#"
+results = moviehash.from_paths([files + "/breakdance.avi", files + "/small.txt"])
+assert results[0] == "8e245d9679d31e12"
+assert isinstance(results[1], moviehash.SmallSizeError)
+"#);
+    }
+}
@@ -0,0 +1,1283 @@
+//! Hashing of files stored without compression inside ZIP archives and
+//! single or multi-volume RAR sets, without extracting them.
+
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::{Error, MovieHash, is_video};
+
+const ZIP_LOCAL_HEADER: u32 = 0x04034b50;
+const ZIP_CENTRAL_HEADER: u32 = 0x02014b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x06064b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: u32 = 0x07064b50;
+
+const RAR4_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x00";
+const RAR5_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x01\x00";
+
+/// Reasons an archive could not be hashed. They are reported as the source
+/// of an [`Error::Io`] whose path is the offending archive volume.
+#[derive(Debug)]
+pub enum ArchiveError {
+    UnknownFormat,
+    Corrupt(&'static str),
+    Compressed { name: String },
+    Encrypted,
+    Unsupported(&'static str),
+    Incomplete { name: String },
+    NoVideo,
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "not a ZIP or RAR archive"),
+            Self::Corrupt(what) => write!(f, "corrupt archive: {}", what),
+            Self::Compressed { name } => write!(f, "archive entry {} is compressed", name),
+            Self::Encrypted => write!(f, "archive is encrypted"),
+            Self::Unsupported(what) => write!(f, "unsupported archive: {}", what),
+            Self::Incomplete { name } => {
+                write!(f, "archive entry {} is missing volumes", name)
+            }
+            Self::NoVideo => write!(f, "archive does not contain a video file"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<ArchiveError> for io::Error {
+    fn from(value: ArchiveError) -> Self {
+        let kind = match value {
+            ArchiveError::UnknownFormat
+            | ArchiveError::Corrupt(_)
+            | ArchiveError::Incomplete { .. } => io::ErrorKind::InvalidData,
+            ArchiveError::Compressed { .. }
+            | ArchiveError::Encrypted
+            | ArchiveError::Unsupported(_) => io::ErrorKind::Unsupported,
+            ArchiveError::NoVideo => io::ErrorKind::NotFound,
+        };
+
+        io::Error::new(kind, value)
+    }
+}
+
+/// A contiguous run of an entry's data inside one archive volume.
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    volume: PathBuf,
+    offset: u64,
+    len: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    name: String,
+    size: u64,
+    stored: bool,
+    segments: Vec<Segment>,
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Uncompressed size of the entry.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Whether the entry is stored without compression and can be hashed.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Opens a reader over the entry's data, spanning volume boundaries.
+    pub fn open(&self) -> Result<EntryReader, Error> {
+        if !self.stored {
+            let name = self.name.clone();
+
+            return Err(at(
+                &self.segments[0].volume,
+                ArchiveError::Compressed { name }.into(),
+            ));
+        }
+
+        Ok(EntryReader {
+            segments: self.segments.clone(),
+            size: self.size,
+            position: 0,
+            current: None,
+        })
+    }
+}
+
+/// The entries of a ZIP archive or a RAR set.
+#[derive(Debug)]
+pub struct Archive {
+    entries: Vec<Entry>,
+}
+
+impl Archive {
+    /// Reads the entry list of the archive at `path`. For multi-volume RAR
+    /// sets `path` must be the first volume; the others are found next to it.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut reader = open_volume(path)?;
+        let mut signature = [0u8; 8];
+        let len = read_up_to(&mut reader, &mut signature).map_err(|e| at(path, e))?;
+
+        let entries = if signature[..len].starts_with(RAR5_SIGNATURE) {
+            rar5::entries(path, reader)?
+        } else if signature[..len].starts_with(RAR4_SIGNATURE) {
+            rar4::entries(path, reader)?
+        } else if signature[..len].starts_with(&ZIP_LOCAL_HEADER.to_le_bytes())
+            || signature[..len].starts_with(&ZIP_END_OF_CENTRAL_DIRECTORY.to_le_bytes())
+        {
+            zip::entries(path, reader.into_inner()).map_err(|e| at(path, e))?
+        } else {
+            return Err(at(path, ArchiveError::UnknownFormat.into()));
+        };
+
+        Ok(Archive { entries })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the largest entry with a video file extension.
+    pub fn video(&self) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| is_video(Path::new(&entry.name)))
+            .max_by_key(|entry| entry.size)
+    }
+}
+
+/// Reads the data of an archive entry as if it were a standalone file.
+#[derive(Debug)]
+pub struct EntryReader {
+    segments: Vec<Segment>,
+    size: u64,
+    position: u64,
+    current: Option<(usize, File)>,
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut start = 0;
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            if self.position >= start + segment.len {
+                start += segment.len;
+                continue;
+            }
+
+            let file = match &mut self.current {
+                Some((current, file)) if *current == index => file,
+                current => &mut current.insert((index, File::open(&segment.volume)?)).1,
+            };
+
+            let within = self.position - start;
+            let len = buf.len().min((segment.len - within) as usize);
+            let offset = segment
+                .offset
+                .checked_add(within)
+                .ok_or(ArchiveError::Corrupt("entry offset"))?;
+            file.seek(SeekFrom::Start(offset))?;
+            let read = file.read(&mut buf[..len])?;
+            self.position += read as u64;
+
+            return Ok(read);
+        }
+
+        Ok(0)
+    }
+}
+
+impl Seek for EntryReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+impl MovieHash {
+    /// Computes the hash of the largest video file inside the ZIP archive or
+    /// RAR set at `path`, which must be stored without compression.
+    pub fn from_archive<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let archive = Archive::open(path)?;
+        let entry = archive
+            .video()
+            .ok_or_else(|| at(path, ArchiveError::NoVideo.into()))?;
+
+        Self::from_archive_entry(entry)
+    }
+
+    /// Computes the hash of a single stored archive entry.
+    pub fn from_archive_entry(entry: &Entry) -> Result<Self, Error> {
+        let reader = entry.open()?;
+
+        Self::from_reader_with_size(reader, entry.size).map_err(|err| match err {
+            Error::SmallSize {
+                path: None,
+                size,
+                min,
+            } => Error::SmallSize {
+                path: Some(PathBuf::from(&entry.name)),
+                size,
+                min,
+            },
+            err => err,
+        })
+    }
+}
+
+fn at(path: &Path, err: io::Error) -> Error {
+    Error::from(err).with_path(path)
+}
+
+fn open_volume(path: &Path) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| at(path, e))
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+
+    while len < buf.len() {
+        match reader.read(&mut buf[len..])? {
+            0 => break,
+            read => len += read,
+        }
+    }
+
+    Ok(len)
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    read_array::<R, 1>(reader).map(|buf| buf[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    read_array(reader).map(u16::from_le_bytes)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    read_array(reader).map(u64::from_le_bytes)
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+
+    Ok(buf)
+}
+
+/// Returns the position of the block following the one at `position`, made
+/// up of `sizes` read from the archive, rejecting sizes that overflow or do
+/// not move forward.
+fn next_block(position: u64, sizes: &[u64]) -> io::Result<u64> {
+    sizes
+        .iter()
+        .try_fold(position, |end, &size| end.checked_add(size))
+        .filter(|&next| next > position)
+        .ok_or_else(|| ArchiveError::Corrupt("block size").into())
+}
+
+/// Maps an unexpected end of file while parsing headers to a corrupt archive.
+fn truncated(err: io::Error) -> io::Error {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        ArchiveError::Corrupt("truncated header").into()
+    } else {
+        err
+    }
+}
+
+/// The part of a file found in one RAR volume.
+struct Part {
+    name: String,
+    size: u64,
+    stored: bool,
+    segment: Segment,
+    split_before: bool,
+    split_after: bool,
+}
+
+/// Collects the entries of a RAR set volume by volume, joining the parts of
+/// files that are split across volumes.
+struct VolumeSet {
+    entries: Vec<Entry>,
+    /// Index into `entries` of the file continued in the next volume.
+    continued: Option<usize>,
+}
+
+impl VolumeSet {
+    fn new() -> Self {
+        VolumeSet {
+            entries: Vec::new(),
+            continued: None,
+        }
+    }
+
+    fn add(&mut self, part: Part) -> io::Result<()> {
+        let Part {
+            name,
+            size,
+            stored,
+            segment,
+            split_before,
+            split_after,
+        } = part;
+
+        let index = match self.continued.take() {
+            Some(index) if split_before && self.entries[index].name == name => {
+                self.entries[index].segments.push(segment);
+                index
+            }
+            _ if split_before => return Err(ArchiveError::Incomplete { name }.into()),
+            Some(index) => {
+                let name = self.entries[index].name.clone();
+                return Err(ArchiveError::Incomplete { name }.into());
+            }
+            None => {
+                self.entries.push(Entry {
+                    name,
+                    size,
+                    stored,
+                    segments: vec![segment],
+                });
+                self.entries.len() - 1
+            }
+        };
+
+        if split_after {
+            self.continued = Some(index);
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> io::Result<Vec<Entry>> {
+        if let Some(index) = self.continued {
+            let name = self.entries[index].name.clone();
+            return Err(ArchiveError::Incomplete { name }.into());
+        }
+
+        for entry in &self.entries {
+            let len = entry
+                .segments
+                .iter()
+                .try_fold(0u64, |len, segment| len.checked_add(segment.len))
+                .ok_or(ArchiveError::Corrupt("entry size"))?;
+
+            if entry.stored && len != entry.size {
+                return Err(ArchiveError::Incomplete {
+                    name: entry.name.clone(),
+                }
+                .into());
+            }
+        }
+
+        Ok(self.entries)
+    }
+}
+
+/// Returns the path of the volume following `path`, using either the
+/// `name.partN.rar` scheme or the older `name.rar`, `name.r00`, `name.r01`
+/// scheme.
+fn next_volume(path: &Path, new_naming: bool) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+
+    let next = if new_naming {
+        let stem = name.strip_suffix(".rar")?;
+        let digits = stem.len() - stem.bytes().rev().take_while(u8::is_ascii_digit).count();
+        let (prefix, number) = stem.split_at(digits);
+        let number: u64 = number.parse().ok()?;
+
+        format!(
+            "{}{:0width$}.rar",
+            prefix,
+            number + 1,
+            width = stem.len() - digits
+        )
+    } else if let Some(stem) = name.strip_suffix(".rar") {
+        format!("{}.r00", stem)
+    } else {
+        let (stem, extension) = name.rsplit_once('.')?;
+        let mut chars = extension.chars();
+        let letter = chars.next()?;
+        let number: u32 = chars.as_str().parse().ok()?;
+
+        match number {
+            99 => format!("{}.{}00", stem, char::from_u32(letter as u32 + 1)?),
+            _ => format!("{}.{}{:02}", stem, letter, number + 1),
+        }
+    };
+
+    Some(path.with_file_name(OsString::from(next)))
+}
+
+mod zip {
+    use super::*;
+
+    struct CentralRecord {
+        name: String,
+        method: u16,
+        flags: u16,
+        compressed_size: u64,
+        size: u64,
+        local_header: u64,
+        disk: u32,
+    }
+
+    pub(super) fn entries(path: &Path, mut file: File) -> io::Result<Vec<Entry>> {
+        let (directory_offset, count) = central_directory(&mut file).map_err(truncated)?;
+        let mut reader = BufReader::new(&mut file);
+        reader.seek(SeekFrom::Start(directory_offset))?;
+
+        let mut records = Vec::new();
+
+        for _ in 0..count {
+            records.push(central_record(&mut reader).map_err(truncated)?);
+        }
+
+        let mut entries = Vec::new();
+
+        for record in records {
+            if record.name.ends_with('/') {
+                continue;
+            }
+
+            if record.disk != 0 {
+                return Err(ArchiveError::Unsupported("split ZIP archives").into());
+            }
+
+            if record.flags & 0x1 != 0 {
+                return Err(ArchiveError::Encrypted.into());
+            }
+
+            reader.seek(SeekFrom::Start(record.local_header))?;
+
+            if read_u32(&mut reader).map_err(truncated)? != ZIP_LOCAL_HEADER {
+                return Err(ArchiveError::Corrupt("missing local file header").into());
+            }
+
+            reader.seek_relative(22)?;
+            let name_len = read_u16(&mut reader).map_err(truncated)?;
+            let extra_len = read_u16(&mut reader).map_err(truncated)?;
+            let offset = record.local_header + 30 + name_len as u64 + extra_len as u64;
+
+            entries.push(Entry {
+                name: record.name,
+                size: record.size,
+                stored: record.method == 0,
+                segments: vec![Segment {
+                    volume: path.to_path_buf(),
+                    offset,
+                    len: record.compressed_size,
+                }],
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Locates the central directory through the end of central directory
+    /// record, following the ZIP64 locator when the sizes overflow.
+    fn central_directory(file: &mut File) -> io::Result<(u64, u64)> {
+        let len = file.seek(SeekFrom::End(0))?;
+        let tail_len = len.min(22 + u16::MAX as u64);
+        file.seek(SeekFrom::Start(len - tail_len))?;
+        let tail = read_bytes(file, tail_len as usize)?;
+
+        let end = (0..tail.len().saturating_sub(21))
+            .rev()
+            .find(|&i| tail[i..i + 4] == ZIP_END_OF_CENTRAL_DIRECTORY.to_le_bytes())
+            .ok_or(ArchiveError::Corrupt("missing end of central directory"))?;
+        let mut record = &tail[end + 4..];
+
+        let disk = read_u16(&mut record)?;
+        record = &record[2..];
+        let _disk_entries = read_u16(&mut record)?;
+        let count = read_u16(&mut record)?;
+        let _directory_size = read_u32(&mut record)?;
+        let offset = read_u32(&mut record)?;
+
+        if disk != 0 {
+            return Err(ArchiveError::Unsupported("split ZIP archives").into());
+        }
+
+        if count != u16::MAX && offset != u32::MAX {
+            return Ok((offset as u64, count as u64));
+        }
+
+        let locator_offset = (len - tail_len + end as u64)
+            .checked_sub(20)
+            .ok_or(ArchiveError::Corrupt("missing ZIP64 locator"))?;
+        file.seek(SeekFrom::Start(locator_offset))?;
+
+        if read_u32(file)? != ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR {
+            return Err(ArchiveError::Corrupt("missing ZIP64 locator").into());
+        }
+
+        file.seek_relative(4)?;
+        let zip64_end = read_u64(file)?;
+        file.seek(SeekFrom::Start(zip64_end))?;
+
+        if read_u32(file)? != ZIP64_END_OF_CENTRAL_DIRECTORY {
+            return Err(ArchiveError::Corrupt("missing ZIP64 end of central directory").into());
+        }
+
+        file.seek_relative(28)?;
+        let count = read_u64(file)?;
+        let _directory_size = read_u64(file)?;
+        let offset = read_u64(file)?;
+
+        Ok((offset, count))
+    }
+
+    fn central_record<R: Read + Seek>(reader: &mut R) -> io::Result<CentralRecord> {
+        if read_u32(reader)? != ZIP_CENTRAL_HEADER {
+            return Err(ArchiveError::Corrupt("missing central directory header").into());
+        }
+
+        reader.seek_relative(4)?;
+        let flags = read_u16(reader)?;
+        let method = read_u16(reader)?;
+        reader.seek_relative(8)?;
+        let mut compressed_size = read_u32(reader)? as u64;
+        let mut size = read_u32(reader)? as u64;
+        let name_len = read_u16(reader)?;
+        let extra_len = read_u16(reader)?;
+        let comment_len = read_u16(reader)?;
+        let mut disk = read_u16(reader)? as u32;
+        reader.seek_relative(6)?;
+        let mut local_header = read_u32(reader)? as u64;
+        let name = String::from_utf8_lossy(&read_bytes(reader, name_len as usize)?).into_owned();
+        let extra = read_bytes(reader, extra_len as usize)?;
+        reader.seek_relative(comment_len as i64)?;
+
+        // The ZIP64 extra field holds, in order, only the values that did
+        // not fit in their regular fields.
+        let mut extra = extra.as_slice();
+
+        while extra.len() >= 4 {
+            let id = read_u16(&mut extra)?;
+            let len = read_u16(&mut extra)? as usize;
+            let mut data = extra
+                .get(..len)
+                .ok_or(ArchiveError::Corrupt("extra field"))?;
+            extra = &extra[len..];
+
+            if id != 0x0001 {
+                continue;
+            }
+
+            if size == u32::MAX as u64 {
+                size = read_u64(&mut data)?;
+            }
+
+            if compressed_size == u32::MAX as u64 {
+                compressed_size = read_u64(&mut data)?;
+            }
+
+            if local_header == u32::MAX as u64 {
+                local_header = read_u64(&mut data)?;
+            }
+
+            if disk == u16::MAX as u32 {
+                disk = read_u32(&mut data)?;
+            }
+        }
+
+        Ok(CentralRecord {
+            name,
+            method,
+            flags,
+            compressed_size,
+            size,
+            local_header,
+            disk,
+        })
+    }
+}
+
+mod rar4 {
+    use super::*;
+
+    const MAIN_HEADER: u8 = 0x73;
+    const FILE_HEADER: u8 = 0x74;
+    const END_OF_ARCHIVE: u8 = 0x7b;
+
+    const MAIN_VOLUME: u16 = 0x0001;
+    const MAIN_NEW_NAMING: u16 = 0x0010;
+    const MAIN_ENCRYPTED_HEADERS: u16 = 0x0080;
+
+    const FILE_SPLIT_BEFORE: u16 = 0x0001;
+    const FILE_SPLIT_AFTER: u16 = 0x0002;
+    const FILE_ENCRYPTED: u16 = 0x0004;
+    const FILE_DIRECTORY: u16 = 0x00e0;
+    const FILE_LARGE: u16 = 0x0100;
+    const FILE_UNICODE: u16 = 0x0200;
+
+    const BLOCK_HAS_DATA: u16 = 0x8000;
+    const END_NEXT_VOLUME: u16 = 0x0001;
+
+    const METHOD_STORE: u8 = 0x30;
+
+    pub(super) fn entries(first: &Path, reader: BufReader<File>) -> Result<Vec<Entry>, Error> {
+        let mut set = VolumeSet::new();
+        let mut volume = first.to_path_buf();
+        let mut reader = reader;
+
+        loop {
+            let next = read_volume(&volume, &mut reader, &mut set).map_err(|e| at(&volume, e))?;
+
+            match next {
+                Some(new_naming) => {
+                    volume = next_volume(&volume, new_naming).ok_or_else(|| {
+                        at(&volume, ArchiveError::Unsupported("volume naming").into())
+                    })?;
+                    reader = open_volume(&volume)?;
+
+                    if read_bytes(&mut reader, RAR4_SIGNATURE.len()).map_err(|e| at(&volume, e))?
+                        != RAR4_SIGNATURE
+                    {
+                        return Err(at(&volume, ArchiveError::UnknownFormat.into()));
+                    }
+                }
+                None => break,
+            }
+        }
+
+        set.finish().map_err(|e| at(first, e))
+    }
+
+    /// Reads the blocks of one volume, returning whether another volume
+    /// follows and if so, whether it uses the `partN.rar` naming.
+    fn read_volume(
+        volume: &Path,
+        reader: &mut BufReader<File>,
+        set: &mut VolumeSet,
+    ) -> io::Result<Option<bool>> {
+        let mut position = reader.seek(SeekFrom::Start(RAR4_SIGNATURE.len() as u64))?;
+        let mut main_flags = 0;
+
+        loop {
+            let mut base = [0u8; 7];
+
+            if read_up_to(reader, &mut base)? < base.len() {
+                // Volumes are not required to end with an end of archive
+                // block, in which case a split file implies a next volume.
+                return Ok(set.continued.map(|_| main_flags & MAIN_NEW_NAMING != 0));
+            }
+
+            let kind = base[2];
+            let flags = u16::from_le_bytes([base[3], base[4]]);
+            let header_size = u16::from_le_bytes([base[5], base[6]]) as u64;
+            let mut data_size = 0;
+
+            if header_size < 7 {
+                return Err(ArchiveError::Corrupt("block header size").into());
+            }
+
+            match kind {
+                MAIN_HEADER => {
+                    main_flags = flags;
+
+                    if flags & MAIN_ENCRYPTED_HEADERS != 0 {
+                        return Err(ArchiveError::Encrypted.into());
+                    }
+
+                    if flags & MAIN_VOLUME == 0 && set.continued.is_some() {
+                        return Err(ArchiveError::Corrupt("volume flag").into());
+                    }
+                }
+                FILE_HEADER => {
+                    let packed_low = read_u32(reader).map_err(truncated)?;
+                    let size_low = read_u32(reader).map_err(truncated)?;
+                    reader.seek_relative(10)?;
+                    let method = read_u8(reader).map_err(truncated)?;
+                    let name_len = read_u16(reader).map_err(truncated)? as usize;
+                    reader.seek_relative(4)?;
+                    let (packed_high, size_high) = if flags & FILE_LARGE != 0 {
+                        (
+                            read_u32(reader).map_err(truncated)?,
+                            read_u32(reader).map_err(truncated)?,
+                        )
+                    } else {
+                        (0, 0)
+                    };
+                    let name = read_bytes(reader, name_len).map_err(truncated)?;
+
+                    data_size = (packed_high as u64) << 32 | packed_low as u64;
+                    let size = (size_high as u64) << 32 | size_low as u64;
+
+                    if flags & FILE_ENCRYPTED != 0 {
+                        return Err(ArchiveError::Encrypted.into());
+                    }
+
+                    if flags & FILE_DIRECTORY != FILE_DIRECTORY {
+                        // Unicode names follow the plain name after a zero byte.
+                        let name = match flags & FILE_UNICODE {
+                            0 => &name[..],
+                            _ => name.split(|&b| b == 0).next().unwrap_or_default(),
+                        };
+
+                        set.add(Part {
+                            name: String::from_utf8_lossy(name).replace('\\', "/"),
+                            size,
+                            stored: method == METHOD_STORE,
+                            segment: Segment {
+                                volume: volume.to_path_buf(),
+                                offset: position + header_size,
+                                len: data_size,
+                            },
+                            split_before: flags & FILE_SPLIT_BEFORE != 0,
+                            split_after: flags & FILE_SPLIT_AFTER != 0,
+                        })?;
+                    }
+                }
+                END_OF_ARCHIVE => {
+                    return Ok(
+                        (flags & END_NEXT_VOLUME != 0).then_some(main_flags & MAIN_NEW_NAMING != 0)
+                    );
+                }
+                _ if flags & BLOCK_HAS_DATA != 0 => {
+                    reader.seek(SeekFrom::Start(position + 7))?;
+                    data_size = read_u32(reader).map_err(truncated)? as u64;
+                }
+                _ => {}
+            }
+
+            let next = next_block(position, &[header_size, data_size])?;
+            position = reader.seek(SeekFrom::Start(next))?;
+        }
+    }
+}
+
+mod rar5 {
+    use super::*;
+
+    const MAIN_HEADER: u64 = 1;
+    const FILE_HEADER: u64 = 2;
+    const ENCRYPTION_HEADER: u64 = 4;
+    const END_OF_ARCHIVE: u64 = 5;
+
+    const HEADER_EXTRA_AREA: u64 = 0x0001;
+    const HEADER_DATA_AREA: u64 = 0x0002;
+    const HEADER_SPLIT_BEFORE: u64 = 0x0008;
+    const HEADER_SPLIT_AFTER: u64 = 0x0010;
+
+    const FILE_DIRECTORY: u64 = 0x0001;
+    const FILE_MTIME: u64 = 0x0002;
+    const FILE_CRC32: u64 = 0x0004;
+
+    const END_NEXT_VOLUME: u64 = 0x0001;
+
+    /// Largest block header RAR5 allows.
+    const MAX_HEADER_SIZE: u64 = 2 * 1024 * 1024;
+
+    pub(super) fn entries(first: &Path, reader: BufReader<File>) -> Result<Vec<Entry>, Error> {
+        let mut set = VolumeSet::new();
+        let mut volume = first.to_path_buf();
+        let mut reader = reader;
+
+        while read_volume(&volume, &mut reader, &mut set).map_err(|e| at(&volume, e))? {
+            volume = next_volume(&volume, true)
+                .ok_or_else(|| at(&volume, ArchiveError::Unsupported("volume naming").into()))?;
+            reader = open_volume(&volume)?;
+
+            if read_bytes(&mut reader, RAR5_SIGNATURE.len()).map_err(|e| at(&volume, e))?
+                != RAR5_SIGNATURE
+            {
+                return Err(at(&volume, ArchiveError::UnknownFormat.into()));
+            }
+        }
+
+        set.finish().map_err(|e| at(first, e))
+    }
+
+    fn read_vint<R: Read>(reader: &mut R) -> io::Result<u64> {
+        let mut value = 0;
+
+        for shift in (0..70).step_by(7) {
+            let byte = read_u8(reader)?;
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(ArchiveError::Corrupt("variable length integer").into())
+    }
+
+    /// Reads the headers of one volume, returning whether another follows.
+    fn read_volume(
+        volume: &Path,
+        reader: &mut BufReader<File>,
+        set: &mut VolumeSet,
+    ) -> io::Result<bool> {
+        reader.seek(SeekFrom::Start(RAR5_SIGNATURE.len() as u64))?;
+
+        loop {
+            let position = reader.stream_position()?;
+            let mut crc = [0u8; 4];
+
+            if read_up_to(reader, &mut crc)? < crc.len() {
+                return Ok(set.continued.is_some());
+            }
+
+            let header_size = read_vint(reader).map_err(truncated)?;
+
+            if header_size > MAX_HEADER_SIZE {
+                return Err(ArchiveError::Corrupt("block header size").into());
+            }
+
+            let header_start = reader.stream_position()?;
+            let header = read_bytes(reader, header_size as usize).map_err(truncated)?;
+            let mut header = header.as_slice();
+
+            let kind = read_vint(&mut header).map_err(truncated)?;
+            let flags = read_vint(&mut header).map_err(truncated)?;
+
+            if flags & HEADER_EXTRA_AREA != 0 {
+                read_vint(&mut header).map_err(truncated)?;
+            }
+
+            let data_size = match flags & HEADER_DATA_AREA {
+                0 => 0,
+                _ => read_vint(&mut header).map_err(truncated)?,
+            };
+            let data_start = header_start
+                .checked_add(header_size)
+                .ok_or(ArchiveError::Corrupt("block header size"))?;
+
+            match kind {
+                MAIN_HEADER => {}
+                ENCRYPTION_HEADER => return Err(ArchiveError::Encrypted.into()),
+                FILE_HEADER => {
+                    let file_flags = read_vint(&mut header).map_err(truncated)?;
+                    let size = read_vint(&mut header).map_err(truncated)?;
+                    read_vint(&mut header).map_err(truncated)?;
+
+                    if file_flags & FILE_MTIME != 0 {
+                        read_u32(&mut header).map_err(truncated)?;
+                    }
+
+                    if file_flags & FILE_CRC32 != 0 {
+                        read_u32(&mut header).map_err(truncated)?;
+                    }
+
+                    let compression = read_vint(&mut header).map_err(truncated)?;
+                    read_vint(&mut header).map_err(truncated)?;
+                    let name_len = read_vint(&mut header).map_err(truncated)?;
+
+                    if name_len > header.len() as u64 {
+                        return Err(ArchiveError::Corrupt("file name length").into());
+                    }
+
+                    let name = read_bytes(&mut header, name_len as usize).map_err(truncated)?;
+                    let method = (compression >> 7) & 0x7;
+
+                    if file_flags & FILE_DIRECTORY == 0 {
+                        set.add(Part {
+                            name: String::from_utf8_lossy(&name).into_owned(),
+                            size,
+                            stored: method == 0,
+                            segment: Segment {
+                                volume: volume.to_path_buf(),
+                                offset: data_start,
+                                len: data_size,
+                            },
+                            split_before: flags & HEADER_SPLIT_BEFORE != 0,
+                            split_after: flags & HEADER_SPLIT_AFTER != 0,
+                        })?;
+                    }
+                }
+                END_OF_ARCHIVE => {
+                    let end_flags = read_vint(&mut header).map_err(truncated)?;
+
+                    return Ok(end_flags & END_NEXT_VOLUME != 0);
+                }
+                _ => {}
+            }
+
+            let next = next_block(position, &[data_start - position, data_size])?;
+            reader.seek(SeekFrom::Start(next))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::{movie, temp_dir};
+
+    fn zip(entries: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+
+        for &(name, method, data) in entries {
+            let offset = archive.len() as u32;
+            let mut header = Vec::new();
+            header.extend_from_slice(&[20, 0, 0, 0]);
+            header.extend_from_slice(&method.to_le_bytes());
+            header.extend_from_slice(&[0; 8]);
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+            archive.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+            archive.extend_from_slice(&header);
+            archive.extend_from_slice(&[0, 0]);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(data);
+
+            directory.extend_from_slice(&ZIP_CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&[20, 0]);
+            directory.extend_from_slice(&header);
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let directory_offset = archive.len() as u32;
+        archive.extend_from_slice(&directory);
+        archive.extend_from_slice(&ZIP_END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        archive.extend_from_slice(&[0; 4]);
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&directory_offset.to_le_bytes());
+        archive.extend_from_slice(&[0, 0]);
+
+        archive
+    }
+
+    /// Writes `data` as a RAR4 set with the old `.rar`, `.r00` naming.
+    fn rar4(dir: &Path, name: &str, data: &[u8], volumes: usize) -> PathBuf {
+        let part_len = data.len().div_ceil(volumes);
+        let mut path = dir.join("movie.rar");
+        let first = path.clone();
+
+        for (i, part) in data.chunks(part_len).enumerate() {
+            let mut volume = RAR4_SIGNATURE.to_vec();
+            volume.extend_from_slice(&[0, 0, 0x73, 0x01, 0x00, 13, 0, 0, 0, 0, 0, 0, 0]);
+
+            let mut flags = 0u16;
+            if i > 0 {
+                flags |= 0x0001;
+            }
+            if i + 1 < volumes {
+                flags |= 0x0002;
+            }
+
+            let header_size = 32 + name.len() as u16;
+            volume.extend_from_slice(&[0, 0, 0x74]);
+            volume.extend_from_slice(&(flags | 0x8000).to_le_bytes());
+            volume.extend_from_slice(&header_size.to_le_bytes());
+            volume.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            volume.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            volume.extend_from_slice(&[0; 9]);
+            volume.extend_from_slice(&[29, 0x30]);
+            volume.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            volume.extend_from_slice(&[0; 4]);
+            volume.extend_from_slice(name.as_bytes());
+            volume.extend_from_slice(part);
+
+            let end_flags: u16 = if i + 1 < volumes { 0x0001 } else { 0 };
+            volume.extend_from_slice(&[0, 0, 0x7b]);
+            volume.extend_from_slice(&end_flags.to_le_bytes());
+            volume.extend_from_slice(&[7, 0]);
+
+            fs::write(&path, volume).unwrap();
+            path = next_volume(&path, false).unwrap();
+        }
+
+        first
+    }
+
+    fn vint(mut value: u64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                bytes.push(byte);
+                return bytes;
+            }
+
+            bytes.push(byte | 0x80);
+        }
+    }
+
+    fn rar5_header(kind: u64, flags: u64, data_size: Option<u64>, fields: &[u8]) -> Vec<u8> {
+        let mut header = vint(kind);
+        header.extend(vint(flags | if data_size.is_some() { 0x2 } else { 0 }));
+        if let Some(data_size) = data_size {
+            header.extend(vint(data_size));
+        }
+        header.extend_from_slice(fields);
+
+        let mut block = vec![0; 4];
+        block.extend(vint(header.len() as u64));
+        block.extend(header);
+        block
+    }
+
+    /// Writes `data` as a RAR5 set named `movie.partN.rar`.
+    fn rar5(dir: &Path, name: &str, data: &[u8], volumes: usize, method: u64) -> PathBuf {
+        let part_len = data.len().div_ceil(volumes);
+        let mut path = dir.join("movie.part01.rar");
+        let first = path.clone();
+
+        for (i, part) in data.chunks(part_len).enumerate() {
+            let mut volume = RAR5_SIGNATURE.to_vec();
+            volume.extend(rar5_header(1, 0, None, &vint(0x1)));
+
+            let mut flags = 0;
+            if i > 0 {
+                flags |= 0x08;
+            }
+            if i + 1 < volumes {
+                flags |= 0x10;
+            }
+
+            let mut fields = vint(0);
+            fields.extend(vint(data.len() as u64));
+            fields.extend(vint(0));
+            fields.extend(vint(method << 7));
+            fields.extend(vint(1));
+            fields.extend(vint(name.len() as u64));
+            fields.extend_from_slice(name.as_bytes());
+            volume.extend(rar5_header(2, flags, Some(part.len() as u64), &fields));
+            volume.extend_from_slice(part);
+
+            let end_flags = if i + 1 < volumes { 0x1 } else { 0 };
+            volume.extend(rar5_header(5, 0, None, &vint(end_flags)));
+
+            fs::write(&path, volume).unwrap();
+            path = next_volume(&path, true).unwrap();
+        }
+
+        first
+    }
+
+    fn archive_error(err: Error) -> ArchiveError {
+        match err {
+            Error::Io { source, .. } => *source.into_inner().unwrap().downcast().unwrap(),
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn should_name_next_volumes() {
+        let next = |name, new_naming| {
+            next_volume(Path::new(name), new_naming).map(|path| path.display().to_string())
+        };
+
+        assert_eq!(next("a/movie.rar", false).unwrap(), "a/movie.r00");
+        assert_eq!(next("movie.r07", false).unwrap(), "movie.r08");
+        assert_eq!(next("movie.r99", false).unwrap(), "movie.s00");
+        assert_eq!(next("movie.part1.rar", true).unwrap(), "movie.part2.rar");
+        assert_eq!(next("movie.part09.rar", true).unwrap(), "movie.part10.rar");
+        assert_eq!(
+            next("movie.part099.rar", true).unwrap(),
+            "movie.part100.rar"
+        );
+    }
+
+    #[test]
+    fn should_hash_stored_zip_entry() {
+        let dir = temp_dir("archive-zip");
+        let path = dir.join("movie.zip");
+        fs::write(
+            &path,
+            zip(&[
+                ("movie.nfo", 0, b"release notes"),
+                ("Sample/sample.avi", 0, &[7; 70000]),
+                ("breakdance.avi", 0, &movie()),
+            ]),
+        )
+        .unwrap();
+
+        let archive = Archive::open(&path).unwrap();
+        assert_eq!(archive.entries().len(), 3);
+        assert_eq!(archive.video().unwrap().name(), "breakdance.avi");
+        assert_eq!(
+            MovieHash::from_archive(&path).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_return_error_for_compressed_entry() {
+        let dir = temp_dir("archive-zip-deflated");
+        let path = dir.join("movie.zip");
+        fs::write(&path, zip(&[("movie.mkv", 8, &[0; 70000])])).unwrap();
+
+        let err = MovieHash::from_archive(&path).unwrap_err();
+        assert_eq!(err.path(), Some(path.as_path()));
+        assert!(matches!(
+            archive_error(err),
+            ArchiveError::Compressed { name } if name == "movie.mkv"
+        ));
+    }
+
+    #[test]
+    fn should_hash_multi_volume_rar4_set() {
+        let dir = temp_dir("archive-rar4");
+        let path = rar4(&dir, "breakdance.avi", &movie(), 3);
+
+        assert_eq!(
+            MovieHash::from_archive(&path).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+        assert_eq!(Archive::open(&path).unwrap().entries()[0].segments.len(), 3);
+    }
+
+    #[test]
+    fn should_hash_multi_volume_rar5_set() {
+        let dir = temp_dir("archive-rar5");
+        let path = rar5(&dir, "breakdance.avi", &movie(), 4, 0);
+
+        assert_eq!(
+            MovieHash::from_archive(&path).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_return_errors_for_compressed_and_incomplete_rar_sets() {
+        let dir = temp_dir("archive-rar5-compressed");
+        let path = rar5(&dir, "breakdance.avi", &movie(), 2, 3);
+        assert!(matches!(
+            archive_error(MovieHash::from_archive(&path).unwrap_err()),
+            ArchiveError::Compressed { .. }
+        ));
+
+        let dir = temp_dir("archive-rar5-incomplete");
+        let path = rar5(&dir, "breakdance.avi", &movie(), 3, 0);
+        fs::remove_file(dir.join("movie.part03.rar")).unwrap();
+        let err = MovieHash::from_archive(&path).unwrap_err();
+        assert_eq!(err.path(), Some(dir.join("movie.part03.rar").as_path()));
+    }
+
+    #[test]
+    fn should_read_entry_across_volumes() {
+        let dir = temp_dir("archive-rar5-reader");
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        let path = rar5(&dir, "data.bin", &data, 3, 0);
+        let archive = Archive::open(path).unwrap();
+        let mut reader = archive.entries()[0].open().unwrap();
+
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+
+        let mut middle = [0u8; 1000];
+        reader.seek(SeekFrom::Start(33_000)).unwrap();
+        reader.read_exact(&mut middle).unwrap();
+        assert_eq!(middle[..], data[33_000..34_000]);
+    }
+
+    #[test]
+    fn should_reject_oversized_rar5_headers() {
+        let dir = temp_dir("archive-rar5-oversized");
+        let path = dir.join("movie.rar");
+
+        let mut volume = RAR5_SIGNATURE.to_vec();
+        volume.extend_from_slice(&[0; 4]);
+        volume.extend(vint(u64::MAX >> 2));
+        fs::write(&path, &volume).unwrap();
+        assert!(matches!(
+            archive_error(Archive::open(&path).unwrap_err()),
+            ArchiveError::Corrupt("block header size")
+        ));
+
+        let mut fields = vint(0);
+        fields.extend(vint(70000));
+        fields.extend(vint(0));
+        fields.extend(vint(0));
+        fields.extend(vint(1));
+        fields.extend(vint(u64::MAX >> 2));
+        let mut volume = RAR5_SIGNATURE.to_vec();
+        volume.extend(rar5_header(2, 0, Some(0), &fields));
+        fs::write(&path, &volume).unwrap();
+        assert!(matches!(
+            archive_error(Archive::open(&path).unwrap_err()),
+            ArchiveError::Corrupt("file name length")
+        ));
+    }
+
+    #[test]
+    fn should_reject_wrapping_block_sizes() {
+        let dir = temp_dir("archive-wrapping");
+
+        let path = dir.join("movie.rar");
+        let mut volume = RAR4_SIGNATURE.to_vec();
+        volume.extend_from_slice(&[0, 0, 0x74]);
+        volume.extend_from_slice(&(0x8000u16 | 0x0100).to_le_bytes());
+        volume.extend_from_slice(&41u16.to_le_bytes());
+        volume.extend_from_slice(&u32::MAX.to_le_bytes());
+        volume.extend_from_slice(&70000u32.to_le_bytes());
+        volume.extend_from_slice(&[0; 9]);
+        volume.extend_from_slice(&[29, 0x30]);
+        volume.extend_from_slice(&1u16.to_le_bytes());
+        volume.extend_from_slice(&[0; 4]);
+        volume.extend_from_slice(&u32::MAX.to_le_bytes());
+        volume.extend_from_slice(&0u32.to_le_bytes());
+        volume.push(b'a');
+        fs::write(&path, &volume).unwrap();
+        assert!(matches!(
+            archive_error(Archive::open(&path).unwrap_err()),
+            ArchiveError::Corrupt("block size")
+        ));
+
+        // Wraps back to the start of the file header.
+        let mut volume = RAR5_SIGNATURE.to_vec();
+        volume.extend(rar5_header(1, 0, Some(u64::MAX - 4), &vint(0)));
+        fs::write(&path, &volume).unwrap();
+        assert!(matches!(
+            archive_error(Archive::open(&path).unwrap_err()),
+            ArchiveError::Corrupt("block size")
+        ));
+    }
+}
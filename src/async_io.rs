@@ -0,0 +1,122 @@
+use std::io::SeekFrom;
+use std::path::Path;
+
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::{CHUNK_SIZE, Error, MovieHash, add_words};
+
+impl MovieHash {
+    /// Asynchronous counterpart of [`MovieHash::from_path`].
+    pub async fn from_path_async<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .await
+            .map_err(|err| Error::from(err).with_path(path))?;
+        let file_size = file
+            .metadata()
+            .await
+            .map_err(|err| Error::from(err).with_path(path))?
+            .len();
+
+        Self::from_async_reader_with_size(file, file_size)
+            .await
+            .map_err(|err| err.with_path(path))
+    }
+
+    /// Asynchronous counterpart of [`MovieHash::from_reader`].
+    pub async fn from_async_reader<R>(mut reader: R) -> Result<Self, Error>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        let size = reader.seek(SeekFrom::End(0)).await.map_err(Error::from)?;
+
+        Self::from_async_reader_with_size(reader, size).await
+    }
+
+    /// Asynchronous counterpart of [`MovieHash::from_reader_with_size`].
+    pub async fn from_async_reader_with_size<R>(mut reader: R, size: u64) -> Result<Self, Error>
+    where
+        R: AsyncRead + AsyncSeek + Unpin,
+    {
+        if size < CHUNK_SIZE {
+            return Err(Error::SmallSize {
+                path: None,
+                size,
+                min: CHUNK_SIZE,
+            });
+        }
+
+        let mut hash: u64 = size;
+        let mut chunk = vec![0u8; CHUNK_SIZE as usize];
+
+        for offset in [0, size - CHUNK_SIZE] {
+            reader
+                .seek(SeekFrom::Start(offset))
+                .await
+                .map_err(Error::from)?;
+            reader.read_exact(&mut chunk).await.map_err(Error::from)?;
+            hash = add_words(hash, &chunk);
+        }
+
+        Ok(MovieHash::new(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_return_same_hash_as_sync_path() {
+        assert_eq!(
+            MovieHash::from_path_async("test-files/breakdance.avi")
+                .await
+                .unwrap(),
+            MovieHash::from_path("test-files/breakdance.avi").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_same_hash_as_sync_reader() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            MovieHash::from_async_reader(io::Cursor::new(&bytes))
+                .await
+                .unwrap(),
+            MovieHash::from_reader(io::Cursor::new(&bytes)).unwrap()
+        );
+        assert_eq!(
+            MovieHash::from_async_reader_with_size(io::Cursor::new(&bytes), bytes.len() as u64)
+                .await
+                .unwrap()
+                .as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_errors_with_path() {
+        let err = MovieHash::from_path_async("test-files/small.txt")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::SmallSize { path: Some(path), size: 20, min: CHUNK_SIZE }
+                if path == Path::new("test-files/small.txt")
+        ));
+
+        let err = MovieHash::from_path_async("test-files/non-existing.mp4")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::Io { path: Some(path), source }
+                if path == Path::new("test-files/non-existing.mp4")
+                    && source.kind() == io::ErrorKind::NotFound
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+use std::ffi::OsString;
+
+pub const USAGE: &str = "\
+Usage: moviehash [OPTIONS] [FILE]...
+
+Print the hash, size and path of each FILE.
+FILE may be a regular file, a directory or a glob pattern. A FILE of -
+is read from standard input, which the shooter algorithm does not support.
+
+Options:
+  -a, --algorithm NAME
+                   hash with NAME, one of opensubtitles (the default),
+                   subdb, napi and shooter
+  -c, --check      read `hash size path` lines from the FILEs and report
+                   whether each listed file is OK, FAILED, MISSING or
+                   SIZE-CHANGED
+  -r, --recursive  hash the files inside directories recursively
+  -j, --json       print one JSON object per file
+  -z, --null       end each output line with NUL instead of newline; with
+                   --check, read NUL-separated lines
+  -h, --help       print this help and exit
+  -V, --version    print version information and exit
+
+Cache options:
+      --cache PATH   reuse the hashes of unchanged files stored in the cache
+                     at PATH and store new ones in it
+      --invalidate   remove the FILEs from the cache instead of hashing them
+      --prune        remove cache entries of files that changed or no longer
+                     exist; FILEs are optional
+      --stats        print cache hit and miss counts to standard error
+
+Exit status is 0 on success, 1 if --check found a file that did not
+verify, 2 on usage errors, 3 if a file could not be read and 4 if a file
+was too small to hash.
+";
+
+/// The fingerprints the command line can hash with.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    #[default]
+    OpenSubtitles,
+    SubDb,
+    Napi,
+    Shooter,
+}
+
+impl Algorithm {
+    fn parse(name: Option<OsString>) -> Result<Self, String> {
+        let name = name.ok_or("option '--algorithm' requires a name")?;
+
+        match name.to_str() {
+            Some("opensubtitles") => Ok(Self::OpenSubtitles),
+            Some("subdb") => Ok(Self::SubDb),
+            Some("napi") => Ok(Self::Napi),
+            Some("shooter") => Ok(Self::Shooter),
+            _ => Err(format!("unknown algorithm '{}'", name.to_string_lossy())),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub algorithm: Algorithm,
+    pub check: bool,
+    pub recursive: bool,
+    pub json: bool,
+    pub null: bool,
+    pub help: bool,
+    pub version: bool,
+    pub cache: Option<OsString>,
+    pub invalidate: bool,
+    pub prune: bool,
+    pub stats: bool,
+    pub paths: Vec<OsString>,
+}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg
+                .to_str()
+                .filter(|arg| arg.starts_with('-') && arg.len() > 1)
+            else {
+                parsed.paths.push(arg);
+                continue;
+            };
+
+            match flag {
+                "--" => parsed.paths.extend(args.by_ref()),
+                "--algorithm" => parsed.algorithm = Algorithm::parse(args.next())?,
+                _ if flag.starts_with("--algorithm=") => {
+                    parsed.algorithm = Algorithm::parse(Some(flag["--algorithm=".len()..].into()))?
+                }
+                "--check" => parsed.check = true,
+                "--recursive" => parsed.recursive = true,
+                "--json" => parsed.json = true,
+                "--null" => parsed.null = true,
+                "--help" => parsed.help = true,
+                "--version" => parsed.version = true,
+                "--cache" => {
+                    parsed.cache = Some(args.next().ok_or("option '--cache' requires a path")?)
+                }
+                _ if flag.starts_with("--cache=") => {
+                    parsed.cache = Some(flag["--cache=".len()..].into())
+                }
+                "--invalidate" => parsed.invalidate = true,
+                "--prune" => parsed.prune = true,
+                "--stats" => parsed.stats = true,
+                _ if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+                _ => {
+                    for short in flag.chars().skip(1) {
+                        match short {
+                            'a' => parsed.algorithm = Algorithm::parse(args.next())?,
+                            'c' => parsed.check = true,
+                            'r' => parsed.recursive = true,
+                            'j' => parsed.json = true,
+                            'z' => parsed.null = true,
+                            'h' => parsed.help = true,
+                            'V' => parsed.version = true,
+                            _ => return Err(format!("unknown option '-{}'", short)),
+                        }
+                    }
+                }
+            }
+        }
+
+        if parsed.help || parsed.version {
+            return Ok(parsed);
+        }
+
+        if parsed.cache.is_none() && (parsed.invalidate || parsed.prune || parsed.stats) {
+            return Err("--invalidate, --prune and --stats require --cache".to_string());
+        }
+
+        if parsed.cache.is_some() && parsed.check {
+            return Err("--cache cannot be used with --check".to_string());
+        }
+
+        if parsed.paths.is_empty() && !parsed.prune {
+            return Err("no files given".to_string());
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn should_parse_long_and_combined_short_flags() {
+        assert_eq!(
+            parse(&["-rz", "--json", "a.mkv", "-", "--", "-b.mkv"]),
+            Ok(Args {
+                recursive: true,
+                json: true,
+                null: true,
+                paths: vec!["a.mkv".into(), "-".into(), "-b.mkv".into()],
+                ..Args::default()
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_check_mode() {
+        assert_eq!(
+            parse(&["-cz", "library.txt"]),
+            Ok(Args {
+                check: true,
+                null: true,
+                paths: vec!["library.txt".into()],
+                ..Args::default()
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_cache_options() {
+        assert_eq!(
+            parse(&["--cache", "hashes.cache", "--prune", "--stats"]),
+            Ok(Args {
+                cache: Some("hashes.cache".into()),
+                prune: true,
+                stats: true,
+                ..Args::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--cache=hashes.cache", "--invalidate", "a.mkv"]).map(|args| args.cache),
+            Ok(Some("hashes.cache".into()))
+        );
+        assert_eq!(
+            parse(&["--prune"]),
+            Err("--invalidate, --prune and --stats require --cache".to_string())
+        );
+        assert_eq!(
+            parse(&["--cache"]),
+            Err("option '--cache' requires a path".to_string())
+        );
+    }
+
+    #[test]
+    fn should_parse_algorithm() {
+        assert_eq!(
+            parse(&["-ra", "subdb", "a.mkv"]).map(|args| (args.algorithm, args.recursive)),
+            Ok((Algorithm::SubDb, true))
+        );
+        assert_eq!(
+            parse(&["--algorithm=shooter", "a.mkv"]).map(|args| args.algorithm),
+            Ok(Algorithm::Shooter)
+        );
+        assert_eq!(
+            parse(&["--algorithm", "md5", "a.mkv"]),
+            Err("unknown algorithm 'md5'".to_string())
+        );
+        assert_eq!(
+            parse(&["-a"]),
+            Err("option '--algorithm' requires a name".to_string())
+        );
+    }
+
+    #[test]
+    fn should_reject_unknown_flags_and_missing_files() {
+        assert_eq!(
+            parse(&["-x", "a.mkv"]),
+            Err("unknown option '-x'".to_string())
+        );
+        assert_eq!(parse(&["--json"]), Err("no files given".to_string()));
+    }
+}
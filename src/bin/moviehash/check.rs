@@ -0,0 +1,303 @@
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use moviehash::{Error, FileFingerprint};
+
+#[derive(Debug, PartialEq)]
+pub struct Entry<F> {
+    pub hash: F,
+    pub size: u64,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Ok,
+    Failed,
+    Missing,
+    SizeChanged,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Ok => write!(f, "OK"),
+            Self::Failed => write!(f, "FAILED"),
+            Self::Missing => write!(f, "MISSING"),
+            Self::SizeChanged => write!(f, "SIZE-CHANGED"),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub ok: usize,
+    pub failed: usize,
+    pub missing: usize,
+    pub size_changed: usize,
+    pub unreadable: usize,
+    pub malformed: usize,
+}
+
+impl Summary {
+    pub fn is_success(&self) -> bool {
+        self.failed + self.missing + self.size_changed + self.unreadable + self.malformed == 0
+    }
+
+    fn count(&mut self, status: &Status) {
+        match status {
+            Status::Ok => self.ok += 1,
+            Status::Failed => self.failed += 1,
+            Status::Missing => self.missing += 1,
+            Status::SizeChanged => self.size_changed += 1,
+        }
+    }
+}
+
+/// Parses a `hash size path` manifest record as printed by `moviehash`.
+pub fn parse_entry<F: FileFingerprint>(record: &[u8]) -> Option<Entry<F>> {
+    let (hash, rest) = split_field(record)?;
+    let (size, path) = split_field(rest)?;
+
+    if path.is_empty() {
+        return None;
+    }
+
+    let hash = F::from_hex(std::str::from_utf8(hash).ok()?)?;
+    let size = std::str::from_utf8(size).ok()?.parse().ok()?;
+
+    Some(Entry {
+        hash,
+        size,
+        path: path_from_bytes(path),
+    })
+}
+
+fn split_field(record: &[u8]) -> Option<(&[u8], &[u8])> {
+    let record = record.trim_ascii_start();
+    let end = record.iter().position(u8::is_ascii_whitespace)?;
+    let (field, rest) = record.split_at(end);
+
+    Some((field, rest.trim_ascii_start()))
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Recomputes the hash of the file described by `entry`. Files whose size
+/// changed are reported without being hashed.
+pub fn verify<F: FileFingerprint>(entry: &Entry<F>) -> Result<Status, Error> {
+    let size = match fs::metadata(&entry.path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Status::Missing),
+        Err(e) => return Err(Error::from(e).with_path(&entry.path)),
+    };
+
+    if size != entry.size {
+        return Ok(Status::SizeChanged);
+    }
+
+    if F::compute_path(&entry.path)? == entry.hash {
+        Ok(Status::Ok)
+    } else {
+        Ok(Status::Failed)
+    }
+}
+
+/// Verifies every record of the manifest at `path`, printing one
+/// `path: STATUS` line per record to `out` and problems to `err`.
+pub fn check_manifest<F, O, E>(
+    path: &Path,
+    null: bool,
+    summary: &mut Summary,
+    out: &mut O,
+    err: &mut E,
+) -> Result<(), Error>
+where
+    F: FileFingerprint,
+    O: Write,
+    E: Write,
+{
+    let manifest = File::open(path).map_err(|e| Error::from(e).with_path(path))?;
+    let mut reader = BufReader::new(manifest);
+    let terminator = if null { b'\0' } else { b'\n' };
+    let mut record = Vec::new();
+    let mut line = 0;
+
+    loop {
+        record.clear();
+        line += 1;
+
+        if reader
+            .read_until(terminator, &mut record)
+            .map_err(|e| Error::from(e).with_path(path))?
+            == 0
+        {
+            return Ok(());
+        }
+
+        let record = record.strip_suffix(&[terminator]).unwrap_or(&record);
+        let record = record.strip_suffix(b"\r").unwrap_or(record);
+
+        if record.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let Some(entry) = parse_entry::<F>(record) else {
+            let _ = writeln!(
+                err,
+                "moviehash: {}: {}: improperly formatted hash line",
+                path.display(),
+                line
+            );
+            summary.malformed += 1;
+            continue;
+        };
+
+        let status = match verify(&entry) {
+            Ok(status) => {
+                summary.count(&status);
+                status
+            }
+            Err(e) => {
+                let _ = writeln!(err, "moviehash: {}", e);
+                summary.unreadable += 1;
+                Status::Failed
+            }
+        };
+
+        let _ = out.write_all(entry.path.as_os_str().as_encoded_bytes());
+        let _ = writeln!(out, ": {}", status);
+    }
+}
+
+/// Prints `sha256sum -c` style warnings for everything that did not verify.
+pub fn report<E: Write>(summary: &Summary, err: &mut E) {
+    let warnings = [
+        (
+            summary.malformed,
+            "line is",
+            "lines are",
+            "improperly formatted",
+        ),
+        (
+            summary.unreadable,
+            "listed file",
+            "listed files",
+            "could not be read",
+        ),
+        (summary.missing, "listed file", "listed files", "missing"),
+        (
+            summary.size_changed,
+            "listed file",
+            "listed files",
+            "changed size",
+        ),
+        (
+            summary.failed,
+            "computed hash",
+            "computed hashes",
+            "did NOT match",
+        ),
+    ];
+
+    for (count, singular, plural, message) in warnings {
+        match count {
+            0 => {}
+            1 => {
+                let _ = writeln!(err, "moviehash: WARNING: 1 {} {}", singular, message);
+            }
+            _ => {
+                let _ = writeln!(err, "moviehash: WARNING: {} {} {}", count, plural, message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use moviehash::{MovieHash, SubDbHash};
+
+    use super::*;
+
+    #[test]
+    fn should_parse_manifest_records() {
+        assert_eq!(
+            parse_entry(b"8e245d9679d31e12  12909756  test-files/break dance.avi"),
+            Some(Entry {
+                hash: MovieHash::new(0x8e245d9679d31e12),
+                size: 12909756,
+                path: PathBuf::from("test-files/break dance.avi"),
+            })
+        );
+        assert_eq!(parse_entry::<MovieHash>(b"8e245d9679d31e12 12909756"), None);
+        assert_eq!(
+            parse_entry::<MovieHash>(b"8e245d9679d31e1 12909756 a.avi"),
+            None
+        );
+        assert_eq!(parse_entry::<MovieHash>(b"8e245d9679d31e12 -1 a.avi"), None);
+        assert_eq!(
+            parse_entry::<SubDbHash>(b"559075d64f311fba4abc08a43b1eff7e 12909756 a.avi")
+                .map(|entry| entry.hash.as_hex()),
+            Some("559075d64f311fba4abc08a43b1eff7e".to_string())
+        );
+    }
+
+    #[test]
+    fn should_report_status_of_each_entry() {
+        let entry = |hash, size, path: &str| Entry {
+            hash: MovieHash::new(hash),
+            size,
+            path: PathBuf::from(path),
+        };
+
+        assert_eq!(
+            verify(&entry(
+                0x8e245d9679d31e12,
+                12909756,
+                "test-files/breakdance.avi"
+            ))
+            .unwrap(),
+            Status::Ok
+        );
+        assert_eq!(
+            verify(&entry(
+                0x8e245d9679d31e13,
+                12909756,
+                "test-files/breakdance.avi"
+            ))
+            .unwrap(),
+            Status::Failed
+        );
+        assert_eq!(
+            verify(&entry(
+                0x8e245d9679d31e12,
+                12909757,
+                "test-files/breakdance.avi"
+            ))
+            .unwrap(),
+            Status::SizeChanged
+        );
+        assert_eq!(
+            verify(&entry(
+                0x8e245d9679d31e12,
+                12909756,
+                "test-files/non-existing.avi"
+            ))
+            .unwrap(),
+            Status::Missing
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Expands a glob pattern supporting `*`, `?` and `[...]` classes into the
+/// sorted list of matching paths.
+///
+/// Operands that exist as given, contain no wildcards or match nothing are
+/// returned unchanged so that they are reported like any other missing file.
+pub fn expand(operand: &OsStr) -> Vec<PathBuf> {
+    let path = Path::new(operand);
+
+    let Some(pattern) = operand.to_str() else {
+        return vec![path.to_path_buf()];
+    };
+
+    if !is_pattern(pattern) || path.exists() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut paths = vec![PathBuf::new()];
+
+    for component in path.components() {
+        let component = match component {
+            Component::Normal(name) => name,
+            other => {
+                paths.iter_mut().for_each(|path| path.push(other));
+                continue;
+            }
+        };
+
+        let name = component.to_str().unwrap_or_default();
+
+        if !is_pattern(name) {
+            paths.iter_mut().for_each(|path| path.push(component));
+            continue;
+        }
+
+        let pattern: Vec<char> = name.chars().collect();
+
+        paths = paths
+            .iter()
+            .flat_map(|dir| {
+                let entries = fs::read_dir(if dir.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    dir
+                });
+
+                entries
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|entry| {
+                        let name = entry.file_name();
+                        let name: Vec<char> = name.to_string_lossy().chars().collect();
+
+                        (name.first() != Some(&'.') || pattern.first() == Some(&'.'))
+                            && matches(&pattern, &name)
+                    })
+                    .map(|entry| dir.join(entry.file_name()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        paths.sort();
+    }
+
+    if paths.is_empty() {
+        return vec![path.to_path_buf()];
+    }
+
+    paths
+}
+
+fn is_pattern(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+/// Matches `name` against `pattern` by backtracking only to the last `*`,
+/// which keeps patterns with many stars linear in practice.
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at.
+    let mut star = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            star = Some((p, n));
+        } else if let Some(len) = pattern.get(p).and_then(|_| single(&pattern[p..], name[n])) {
+            p += len;
+            n += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p;
+            n = star_n + 1;
+            star = Some((star_p, n));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns how many characters of `pattern`, which does not start with `*`,
+/// match the single character `c`.
+fn single(pattern: &[char], c: char) -> Option<usize> {
+    match pattern[0] {
+        '?' => Some(1),
+        '[' => match class(&pattern[1..]) {
+            Some(class) => (class.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+                != class.negated)
+                .then_some(pattern.len() - class.rest.len()),
+            None => (c == '[').then_some(1),
+        },
+        literal => (c == literal).then_some(1),
+    }
+}
+
+struct Class<'a> {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+    rest: &'a [char],
+}
+
+/// Parses the body of a `[...]` class, returning `None` if it is never closed.
+fn class(pattern: &[char]) -> Option<Class<'_>> {
+    let (negated, mut i) = match pattern.first() {
+        Some('!' | '^') => (true, 1),
+        _ => (false, 0),
+    };
+    let start = i;
+    let mut ranges = Vec::new();
+
+    while i < pattern.len() {
+        let c = pattern[i];
+
+        if c == ']' && i > start {
+            return Some(Class {
+                ranges,
+                negated,
+                rest: &pattern[i + 1..],
+            });
+        }
+
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&hi| hi != ']') {
+            ranges.push((c, pattern[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_matches(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+
+        matches(&pattern, &name)
+    }
+
+    #[test]
+    fn should_match_wildcards_and_classes() {
+        assert!(glob_matches("*.avi", "breakdance.avi"));
+        assert!(glob_matches("b?eak*", "breakdance.avi"));
+        assert!(glob_matches("[a-c]reak[!x]ance.avi", "breakdance.avi"));
+        assert!(glob_matches("[]]", "]"));
+        assert!(glob_matches("[", "["));
+        assert!(!glob_matches("*.mkv", "breakdance.avi"));
+        assert!(!glob_matches("[!b]*", "breakdance.avi"));
+        assert!(glob_matches("*a*", "breakdance.avi"));
+        assert!(glob_matches("b*a*a*.avi", "breakdance.avi"));
+        assert!(glob_matches("**", ""));
+        assert!(!glob_matches("?", ""));
+    }
+
+    #[test]
+    fn should_match_many_stars_in_linear_time() {
+        let name = "a".repeat(10_000);
+
+        assert!(!glob_matches("*a*a*a*a*a*a*a*a*b", &name));
+        assert!(glob_matches("*a*a*a*a*a*a*a*a*", &name));
+    }
+
+    #[test]
+    fn should_expand_patterns_against_file_system() {
+        assert_eq!(
+            expand(OsStr::new("test-files/*")),
+            vec![
+                PathBuf::from("test-files/breakdance.avi"),
+                PathBuf::from("test-files/small.txt")
+            ]
+        );
+        assert_eq!(
+            expand(OsStr::new("test-*/*.mkv")),
+            vec![PathBuf::from("test-*/*.mkv")]
+        );
+    }
+}
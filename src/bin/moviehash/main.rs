@@ -0,0 +1,506 @@
+mod args;
+mod check;
+mod glob;
+mod output;
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use moviehash::cache::HashCache;
+use moviehash::{Error, FileFingerprint, MovieHash, NapiHash, ShooterHash, SubDbHash};
+
+use args::{Algorithm, Args};
+use output::Output;
+
+const EXIT_CHECK_FAILED: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_IO: u8 = 3;
+const EXIT_SMALL_SIZE: u8 = 4;
+
+fn main() -> ExitCode {
+    ExitCode::from(run(
+        std::env::args_os().skip(1),
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+        &mut io::stderr().lock(),
+    ))
+}
+
+fn run<I, R, O, E>(args: I, input: &mut R, out: &mut O, err: &mut E) -> u8
+where
+    I: IntoIterator<Item = OsString>,
+    R: Read,
+    O: Write,
+    E: Write,
+{
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(message) => {
+            let _ = write!(err, "moviehash: {}\n\n{}", message, args::USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    if args.help {
+        let _ = write!(out, "{}", args::USAGE);
+        return 0;
+    }
+
+    if args.version {
+        let _ = writeln!(out, "moviehash {}", env!("CARGO_PKG_VERSION"));
+        return 0;
+    }
+
+    match args.algorithm {
+        Algorithm::OpenSubtitles => run_algorithm::<MovieHash, _, _, _>(&args, input, out, err),
+        Algorithm::SubDb => run_algorithm::<SubDbHash, _, _, _>(&args, input, out, err),
+        Algorithm::Napi => run_algorithm::<NapiHash, _, _, _>(&args, input, out, err),
+        Algorithm::Shooter => run_algorithm::<ShooterHash, _, _, _>(&args, input, out, err),
+    }
+}
+
+fn run_algorithm<F, R, O, E>(args: &Args, input: &mut R, out: &mut O, err: &mut E) -> u8
+where
+    F: FileFingerprint,
+    R: Read,
+    O: Write,
+    E: Write,
+{
+    if args.check {
+        return check::<F, _, _>(args, out, err);
+    }
+
+    let mut cache = match args.cache.as_ref().map(HashCache::<F>::open).transpose() {
+        Ok(cache) => cache,
+        Err(e) => {
+            let _ = writeln!(err, "moviehash: {}", e);
+            return EXIT_IO;
+        }
+    };
+
+    if let Some(cache) = cache.as_mut().filter(|_| args.prune) {
+        let removed = cache.prune();
+        let _ = writeln!(err, "moviehash: pruned {} cache entries", removed);
+    }
+
+    let mut status = match cache.as_mut().filter(|_| args.invalidate) {
+        Some(cache) => invalidate(args, cache, err),
+        None => hash_all(args, cache.as_mut(), input, out, err),
+    };
+
+    if let Some(mut cache) = cache {
+        if args.stats {
+            let stats = cache.stats();
+            let _ = writeln!(
+                err,
+                "moviehash: cache: {} hits, {} misses, {} entries",
+                stats.hits,
+                stats.misses,
+                cache.len()
+            );
+        }
+
+        if let Err(e) = cache.save() {
+            let _ = writeln!(err, "moviehash: {}", e);
+
+            if status == 0 {
+                status = EXIT_IO;
+            }
+        }
+    }
+
+    status
+}
+
+fn hash_all<F: FileFingerprint, R: Read, O: Write, E: Write>(
+    args: &Args,
+    mut cache: Option<&mut HashCache<F>>,
+    input: &mut R,
+    out: &mut O,
+    err: &mut E,
+) -> u8 {
+    let mut output = Output::new(out, args.json, args.null);
+    let mut status = 0;
+
+    for operand in &args.paths {
+        if operand == "-" {
+            let stdin = Path::new("-");
+            let result = hash_stream::<F, _>(input).and_then(|(hash, size)| {
+                output
+                    .write(&hash, size, stdin)
+                    .map_err(|e| Error::from(e).with_path(stdin))
+            });
+            report(result, &mut status, err);
+            continue;
+        }
+
+        for path in glob::expand(operand) {
+            visit(&path, args.recursive, &mut |entry| {
+                let result = entry.and_then(|path| {
+                    let (hash, size) = hash::<F>(path, cache.as_deref_mut())?;
+
+                    output
+                        .write(&hash, size, path)
+                        .map_err(|e| Error::from(e).with_path(path))
+                });
+                report(result, &mut status, err);
+            });
+        }
+    }
+
+    if let Err(e) = output.flush() {
+        let _ = writeln!(err, "moviehash: {}", e);
+
+        if status == 0 {
+            status = EXIT_IO;
+        }
+    }
+
+    status
+}
+
+/// Prints the error of `result`, if any, and makes it the exit status unless
+/// an earlier file already failed.
+fn report<E: Write>(result: Result<(), Error>, status: &mut u8, err: &mut E) {
+    if let Err(e) = result {
+        let _ = writeln!(err, "moviehash: {}", e);
+
+        if *status == 0 {
+            *status = exit_code(&e);
+        }
+    }
+}
+
+fn invalidate<F: FileFingerprint, E: Write>(
+    args: &Args,
+    cache: &mut HashCache<F>,
+    err: &mut E,
+) -> u8 {
+    let mut removed = 0;
+    let mut status = 0;
+
+    for operand in &args.paths {
+        for path in glob::expand(operand) {
+            visit(&path, args.recursive, &mut |entry| match entry {
+                Ok(path) => removed += cache.invalidate(path),
+                Err(e) => {
+                    let _ = writeln!(err, "moviehash: {}", e);
+                    status = EXIT_IO;
+                }
+            });
+        }
+    }
+
+    let _ = writeln!(err, "moviehash: invalidated {} cache entries", removed);
+
+    status
+}
+
+fn check<F: FileFingerprint, O: Write, E: Write>(args: &Args, out: &mut O, err: &mut E) -> u8 {
+    let mut summary = check::Summary::default();
+    let mut status = 0;
+
+    for manifest in &args.paths {
+        if let Err(e) =
+            check::check_manifest::<F, _, _>(Path::new(manifest), args.null, &mut summary, out, err)
+        {
+            let _ = writeln!(err, "moviehash: {}", e);
+            status = EXIT_IO;
+        }
+    }
+
+    check::report(&summary, err);
+
+    if status == 0 && !summary.is_success() {
+        status = EXIT_CHECK_FAILED;
+    }
+
+    status
+}
+
+fn hash<F: FileFingerprint>(
+    path: &Path,
+    cache: Option<&mut HashCache<F>>,
+) -> Result<(F, u64), Error> {
+    let file = File::open(path).map_err(|e| Error::from(e).with_path(path))?;
+    let metadata = file
+        .metadata()
+        .map_err(|e| Error::from(e).with_path(path))?;
+
+    if metadata.is_dir() {
+        return Err(Error::from(io::Error::from(io::ErrorKind::IsADirectory)).with_path(path));
+    }
+
+    let hash = match cache {
+        Some(cache) => cache.hash(path)?,
+        None => F::compute_file(&file).map_err(|e| e.with_path(path))?,
+    };
+
+    Ok((hash, metadata.len()))
+}
+
+/// Hashes a forward-only `input`, returning the hash along with its length.
+fn hash_stream<F: FileFingerprint, R: Read>(input: &mut R) -> Result<(F, u64), Error> {
+    let mut input = Counter {
+        inner: input,
+        count: 0,
+    };
+    let hash = F::compute_stream(&mut input).map_err(|e| e.with_path("-"))?;
+    // Hashes of the start of a stream stop reading before its end.
+    io::copy(&mut input, &mut io::sink()).map_err(|e| Error::from(e).with_path("-"))?;
+
+    Ok((hash, input.count))
+}
+
+/// Counts the bytes read through it.
+struct Counter<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for Counter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+
+        Ok(read)
+    }
+}
+
+/// Calls `f` with `path`, or with every file below it in sorted order when it
+/// is a directory and `recursive` is set.
+fn visit(path: &Path, recursive: bool, f: &mut dyn FnMut(Result<&Path, Error>)) {
+    if !recursive || !path.is_dir() {
+        return f(Ok(path));
+    }
+
+    match read_dir_sorted(path) {
+        Ok(paths) => {
+            for path in paths {
+                visit(&path, recursive, f);
+            }
+        }
+        Err(e) => f(Err(Error::from(e).with_path(path))),
+    }
+}
+
+fn read_dir_sorted(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    Ok(paths)
+}
+
+fn exit_code(err: &Error) -> u8 {
+    match err {
+        Error::SmallSize { .. } => EXIT_SMALL_SIZE,
+        Error::Io { .. } => EXIT_IO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_with(args: &[&str]) -> (u8, String, String) {
+        run_with_input(args, &[])
+    }
+
+    fn run_with_input(args: &[&str], mut input: &[u8]) -> (u8, String, String) {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let status = run(
+            args.iter().map(OsString::from),
+            &mut input,
+            &mut out,
+            &mut err,
+        );
+
+        (
+            status,
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_print_hash_size_and_path() {
+        assert_eq!(
+            run_with(&["test-files/breakdance.avi"]),
+            (
+                0,
+                "8e245d9679d31e12  12909756  test-files/breakdance.avi\n".to_string(),
+                String::new()
+            )
+        );
+    }
+
+    #[test]
+    fn should_hash_with_chosen_algorithm() {
+        assert_eq!(
+            run_with(&["-a", "subdb", "test-files/breakdance.avi"]).1,
+            "559075d64f311fba4abc08a43b1eff7e  12909756  test-files/breakdance.avi\n"
+        );
+        assert_eq!(
+            run_with(&["--algorithm=napi", "test-files/small.txt"]),
+            (
+                0,
+                "90b6d5f31731fc08a143b7a06e14b112  20  test-files/small.txt\n".to_string(),
+                String::new()
+            )
+        );
+    }
+
+    #[test]
+    fn should_hash_standard_input() {
+        let movie = fs::read("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            run_with_input(&["-", "test-files/breakdance.avi"], &movie),
+            (
+                0,
+                "8e245d9679d31e12  12909756  -\n\
+                 8e245d9679d31e12  12909756  test-files/breakdance.avi\n"
+                    .to_string(),
+                String::new()
+            )
+        );
+        assert_eq!(
+            run_with_input(&["-a", "napi", "-"], &movie).1,
+            "4769470d771c91921469fcb8ecd691f8  12909756  -\n"
+        );
+        assert_eq!(
+            run_with_input(&["-"], b"too small"),
+            (
+                EXIT_SMALL_SIZE,
+                String::new(),
+                "moviehash: -: file size of 9 bytes is less than 65536 bytes\n".to_string()
+            )
+        );
+        assert_eq!(
+            run_with_input(&["-a", "shooter", "-"], &movie),
+            (
+                EXIT_IO,
+                String::new(),
+                "moviehash: -: shooter hashes cannot be computed from a stream\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn should_print_json_with_null_separators() {
+        let (status, out, _) = run_with(&["--json", "-z", "test-files/breakdance.avi"]);
+
+        assert_eq!(status, 0);
+        assert_eq!(
+            out,
+            "{\"hash\":\"8e245d9679d31e12\",\"size\":12909756,\"path\":\"test-files/breakdance.avi\"}\0"
+        );
+    }
+
+    #[test]
+    fn should_walk_directories_recursively_and_expand_globs() {
+        let (status, out, err) = run_with(&["-r", "test-files"]);
+
+        assert_eq!(status, EXIT_SMALL_SIZE);
+        assert_eq!(
+            out,
+            "8e245d9679d31e12  12909756  test-files/breakdance.avi\n"
+        );
+        assert!(err.contains("test-files/small.txt"));
+
+        assert_eq!(run_with(&["test-files/*.avi"]).1, out);
+    }
+
+    #[test]
+    fn should_return_exit_code_for_each_error() {
+        assert_eq!(run_with(&["test-files"]).0, EXIT_IO);
+        assert_eq!(run_with(&["test-files/non-existing.mp4"]).0, EXIT_IO);
+        assert_eq!(run_with(&["test-files/small.txt"]).0, EXIT_SMALL_SIZE);
+        assert_eq!(run_with(&["--bogus"]).0, EXIT_USAGE);
+    }
+
+    #[test]
+    fn should_reuse_and_prune_cached_hashes() {
+        let cache =
+            std::env::temp_dir().join(format!("moviehash-{}-cli-cache", std::process::id()));
+        let _ = fs::remove_file(&cache);
+        let cache = cache.to_str().unwrap();
+
+        let (status, out, err) =
+            run_with(&["--cache", cache, "--stats", "test-files/breakdance.avi"]);
+        assert_eq!(status, 0);
+        assert_eq!(
+            out,
+            "8e245d9679d31e12  12909756  test-files/breakdance.avi\n"
+        );
+        assert_eq!(err, "moviehash: cache: 0 hits, 1 misses, 1 entries\n");
+
+        let (_, second_out, err) = run_with(&["--cache", cache, "--stats", "test-files/*.avi"]);
+        assert_eq!(second_out, out);
+        assert_eq!(err, "moviehash: cache: 1 hits, 0 misses, 1 entries\n");
+
+        let (status, out, err) = run_with(&["--cache", cache, "--invalidate", "test-files"]);
+        assert_eq!((status, out.as_str()), (0, ""));
+        assert_eq!(err, "moviehash: invalidated 0 cache entries\n");
+
+        let (_, _, err) = run_with(&[
+            "--cache",
+            cache,
+            "--prune",
+            "--invalidate",
+            "-r",
+            "test-files",
+        ]);
+        assert_eq!(
+            err,
+            "moviehash: pruned 0 cache entries\nmoviehash: invalidated 1 cache entries\n"
+        );
+
+        fs::remove_file(cache).unwrap();
+    }
+
+    #[test]
+    fn should_check_manifest() {
+        let manifest = std::env::temp_dir().join(format!(
+            "moviehash-{}-check-manifest.txt",
+            std::process::id()
+        ));
+        fs::write(
+            &manifest,
+            "8e245d9679d31e12  12909756  test-files/breakdance.avi\n\
+             0000000000000000  12909756  test-files/breakdance.avi\n\
+             8e245d9679d31e12  42  test-files/breakdance.avi\n\
+             8e245d9679d31e12  12909756  test-files/non-existing.avi\n\
+             not a hash line\n",
+        )
+        .unwrap();
+        let result = run_with(&["--check", manifest.to_str().unwrap()]);
+        fs::remove_file(&manifest).unwrap();
+
+        assert_eq!(
+            result,
+            (
+                EXIT_CHECK_FAILED,
+                "test-files/breakdance.avi: OK\n\
+                 test-files/breakdance.avi: FAILED\n\
+                 test-files/breakdance.avi: SIZE-CHANGED\n\
+                 test-files/non-existing.avi: MISSING\n"
+                    .to_string(),
+                format!(
+                    "moviehash: {}: 5: improperly formatted hash line\n\
+                     moviehash: WARNING: 1 line is improperly formatted\n\
+                     moviehash: WARNING: 1 listed file missing\n\
+                     moviehash: WARNING: 1 listed file changed size\n\
+                     moviehash: WARNING: 1 computed hash did NOT match\n",
+                    manifest.display()
+                )
+            )
+        );
+    }
+}
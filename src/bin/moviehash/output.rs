@@ -0,0 +1,71 @@
+use std::fmt::Display;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub struct Output<W: Write> {
+    writer: BufWriter<W>,
+    json: bool,
+    terminator: u8,
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(writer: W, json: bool, null: bool) -> Self {
+        Output {
+            writer: BufWriter::new(writer),
+            json,
+            terminator: if null { b'\0' } else { b'\n' },
+        }
+    }
+
+    pub fn write<H: Display>(&mut self, hash: &H, size: u64, path: &Path) -> io::Result<()> {
+        if self.json {
+            write!(
+                self.writer,
+                "{{\"hash\":\"{}\",\"size\":{},\"path\":\"{}\"}}",
+                hash,
+                size,
+                escape_json(&path.to_string_lossy())
+            )?;
+        } else {
+            write!(self.writer, "{}  {}  ", hash, size)?;
+            self.writer.write_all(path.as_os_str().as_encoded_bytes())?;
+        }
+
+        self.writer.write_all(&[self.terminator])
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_escape_json_strings() {
+        assert_eq!(
+            escape_json("a \"b\"\\c\n\u{1}"),
+            "a \\\"b\\\"\\\\c\\n\\u0001"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::{Error, FileFingerprint, MovieHash};
+
+const HEADER: &str = "moviehash-cache 1";
+
+/// Identifies the contents of a file without reading it. A file whose key is
+/// unchanged is assumed to still have the cached hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub device: u64,
+    pub inode: u64,
+    pub size: u64,
+    /// Modification time in nanoseconds since the Unix epoch.
+    pub mtime: i128,
+}
+
+impl Key {
+    /// Builds the key of a file from its metadata. Outside Unix the device
+    /// and inode are not available and are always zero.
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let mtime = match metadata
+            .modified()
+            .map(|time| time.duration_since(UNIX_EPOCH))
+        {
+            Ok(Ok(duration)) => duration.as_nanos() as i128,
+            Ok(Err(err)) => -(err.duration().as_nanos() as i128),
+            Err(_) => 0,
+        };
+
+        #[cfg(unix)]
+        let (device, inode) = {
+            use std::os::unix::fs::MetadataExt;
+
+            (metadata.dev(), metadata.ino())
+        };
+        #[cfg(not(unix))]
+        let (device, inode) = (0, 0);
+
+        Key {
+            device,
+            inode,
+            size: metadata.len(),
+            mtime,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug)]
+struct Record {
+    /// The hash as written by [`FileFingerprint::to_hex`].
+    hash: String,
+    path: PathBuf,
+}
+
+/// A persistent store of hashes keyed by device, inode, size and
+/// modification time, so that unchanged files are not read again.
+///
+/// Each cache file holds the hashes of a single [`FileFingerprint`], named in
+/// its header.
+#[derive(Debug)]
+pub struct HashCache<F = MovieHash> {
+    path: PathBuf,
+    records: HashMap<Key, Record>,
+    stats: Stats,
+    dirty: bool,
+    fingerprint: PhantomData<fn() -> F>,
+}
+
+impl<F: FileFingerprint> HashCache<F> {
+    /// Loads the cache stored at `path`, starting empty if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut cache = HashCache {
+            path: path.to_path_buf(),
+            records: HashMap::new(),
+            stats: Stats::default(),
+            dirty: false,
+            fingerprint: PhantomData,
+        };
+
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(cache),
+            Err(err) => return Err(Error::from(err).with_path(path)),
+        };
+
+        cache
+            .load(BufReader::new(file))
+            .map_err(|err| Error::from(err).with_path(path))?;
+
+        Ok(cache)
+    }
+
+    fn load<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = reader.split(b'\n');
+
+        match lines.next().transpose()? {
+            Some(header) if header == Self::header().as_bytes() => {}
+            None => return Ok(()),
+            Some(_) => return Err(invalid("unrecognized hash cache format")),
+        }
+
+        for line in lines {
+            let line = line?;
+            let mut fields = line.splitn(6, |&b| b == b' ');
+            let mut field = || -> io::Result<&str> {
+                fields
+                    .next()
+                    .and_then(|field| std::str::from_utf8(field).ok())
+                    .ok_or_else(|| invalid("malformed hash cache record"))
+            };
+            let malformed = |_| invalid("malformed hash cache record");
+
+            let key = Key {
+                device: field()?.parse().map_err(malformed)?,
+                inode: field()?.parse().map_err(malformed)?,
+                size: field()?.parse().map_err(malformed)?,
+                mtime: field()?.parse().map_err(malformed)?,
+            };
+            let hash = field()?;
+            F::from_hex(hash).ok_or_else(|| invalid("malformed hash cache record"))?;
+            let path = fields
+                .next()
+                .and_then(unescape)
+                .ok_or_else(|| invalid("malformed hash cache record"))?;
+
+            self.records.insert(
+                key,
+                Record {
+                    hash: hash.to_string(),
+                    path,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn header() -> String {
+        format!("{} {}", HEADER, F::NAME)
+    }
+
+    /// Returns the cached hash of the file at `path` if its key is unchanged,
+    /// otherwise computes it with [`FileFingerprint::compute_path`] and caches
+    /// it.
+    pub fn hash<P: AsRef<Path>>(&mut self, path: P) -> Result<F, Error> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path).map_err(|err| Error::from(err).with_path(path))?;
+        let key = Key::from_metadata(&metadata);
+
+        // Without inodes the key alone cannot tell two files apart.
+        if let Some(record) = self.records.get(&key)
+            && (cfg!(unix) || record.path == path)
+            && let Some(hash) = F::from_hex(&record.hash)
+        {
+            self.stats.hits += 1;
+            return Ok(hash);
+        }
+
+        self.stats.misses += 1;
+        let hash = F::compute_path(path)?;
+        self.records.insert(
+            key,
+            Record {
+                hash: hash.to_hex(),
+                path: path.to_path_buf(),
+            },
+        );
+        self.dirty = true;
+
+        Ok(hash)
+    }
+
+    /// Removes every entry recorded for `path` or matching its current key,
+    /// returning how many were removed.
+    pub fn invalidate<P: AsRef<Path>>(&mut self, path: P) -> usize {
+        let path = path.as_ref();
+        let key = fs::metadata(path)
+            .ok()
+            .map(|metadata| Key::from_metadata(&metadata));
+
+        self.retain(|record_key, record| Some(*record_key) != key && record.path != path)
+    }
+
+    /// Removes entries whose file no longer exists or has changed since it was
+    /// hashed, returning how many were removed.
+    pub fn prune(&mut self) -> usize {
+        self.retain(|key, record| {
+            fs::metadata(&record.path).is_ok_and(|metadata| Key::from_metadata(&metadata) == *key)
+        })
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    fn retain<P: FnMut(&Key, &Record) -> bool>(&mut self, mut f: P) -> usize {
+        let len = self.records.len();
+        self.records.retain(|key, record| f(key, record));
+        let removed = len - self.records.len();
+        self.dirty |= removed > 0;
+
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Hit and miss counts of [`HashCache::hash`] since the cache was opened.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Writes the cache back to its file if it changed. The file is replaced
+    /// atomically so that an interrupted save does not corrupt it.
+    pub fn save(&mut self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+
+        self.write(&temp)
+            .and_then(|_| fs::rename(&temp, &self.path))
+            .map_err(|err| Error::from(err).with_path(&self.path))?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    fn write(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writeln!(writer, "{}", Self::header())?;
+
+        for (key, record) in &self.records {
+            write!(
+                writer,
+                "{} {} {} {} {} ",
+                key.device, key.inode, key.size, key.mtime, record.hash
+            )?;
+            writer.write_all(&escape(&record.path))?;
+            writer.write_all(b"\n")?;
+        }
+
+        writer.into_inner().map_err(io::Error::from)?.sync_all()
+    }
+}
+
+fn escape(path: &Path) -> Vec<u8> {
+    let mut escaped = Vec::new();
+
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'\\' => escaped.extend_from_slice(b"\\\\"),
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            byte => escaped.push(byte),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(escaped: &[u8]) -> Option<PathBuf> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut iter = escaped.iter();
+
+    while let Some(&byte) = iter.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+
+        match iter.next() {
+            Some(b'\\') => bytes.push(b'\\'),
+            Some(b'n') => bytes.push(b'\n'),
+            _ => return None,
+        }
+    }
+
+    path_from_bytes(bytes)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    Some(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShooterHash;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn should_return_cached_hash_until_file_changes() {
+        let dir = temp_dir("cache-hits");
+        let movie = dir.join("movie\\with\nodd name.avi");
+        fs::write(&movie, vec![1; 70000]).unwrap();
+
+        let mut cache: HashCache = HashCache::open(dir.join("cache")).unwrap();
+        let hash = cache.hash(&movie).unwrap();
+        assert_eq!(cache.hash(&movie).unwrap(), hash);
+        assert_eq!(cache.stats(), Stats { hits: 1, misses: 1 });
+        cache.save().unwrap();
+
+        let mut cache: HashCache = HashCache::open(dir.join("cache")).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.hash(&movie).unwrap(), hash);
+        assert_eq!(cache.stats(), Stats { hits: 1, misses: 0 });
+
+        fs::write(&movie, vec![2; 70008]).unwrap();
+        assert_ne!(cache.hash(&movie).unwrap(), hash);
+        assert_eq!(cache.stats(), Stats { hits: 1, misses: 1 });
+    }
+
+    #[test]
+    fn should_invalidate_and_prune_entries() {
+        let dir = temp_dir("cache-prune");
+        let [kept, removed, changed] = ["kept.avi", "removed.avi", "changed.avi"].map(|name| {
+            let path = dir.join(name);
+            fs::write(&path, vec![name.len() as u8; 70000]).unwrap();
+            path
+        });
+
+        let mut cache: HashCache = HashCache::open(dir.join("cache")).unwrap();
+        for path in [&kept, &removed, &changed] {
+            cache.hash(path).unwrap();
+        }
+
+        fs::remove_file(&removed).unwrap();
+        fs::write(&changed, vec![0; 80000]).unwrap();
+        assert_eq!(cache.prune(), 2);
+        assert_eq!(cache.invalidate(&kept), 1);
+        assert!(cache.is_empty());
+
+        cache.hash(&kept).unwrap();
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn should_keep_fingerprints_in_separate_caches() {
+        let dir = temp_dir("cache-fingerprints");
+        let movie = Path::new("test-files/breakdance.avi");
+
+        let mut cache = HashCache::<ShooterHash>::open(dir.join("cache")).unwrap();
+        assert_eq!(
+            cache.hash(movie).unwrap(),
+            ShooterHash::from_path(movie).unwrap()
+        );
+        cache.save().unwrap();
+
+        let mut cache = HashCache::<ShooterHash>::open(dir.join("cache")).unwrap();
+        cache.hash(movie).unwrap();
+        assert_eq!(cache.stats(), Stats { hits: 1, misses: 0 });
+        assert!(HashCache::<MovieHash>::open(dir.join("cache")).is_err());
+    }
+
+    #[test]
+    fn should_reject_unrecognized_cache_file() {
+        let dir = temp_dir("cache-format");
+        fs::write(dir.join("cache"), "something else\n").unwrap();
+
+        let err = HashCache::<MovieHash>::open(dir.join("cache")).unwrap_err();
+        assert_eq!(err.path(), Some(dir.join("cache").as_path()));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{}: unrecognized hash cache format",
+                dir.join("cache").display()
+            )
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use md4::{Digest, Md4};
+
+use crate::Error;
+
+/// Size of the blocks whose MD4 digests make up the hash.
+const ED2K_BLOCK_SIZE: u64 = 9_728_000;
+
+/// How to hash files whose size is an exact multiple of the block size.
+///
+/// The original eDonkey client appended the digest of an empty block to such
+/// files ("red"), while later clients and AniDB do not ("blue"). Both give the
+/// same hash for every other size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Ed2kVariant {
+    #[default]
+    Blue,
+    Red,
+}
+
+/// The eDonkey2000 hash used by eMule and AniDB, along with the size of the
+/// file it was computed from. Unlike [`crate::MovieHash`] it covers the
+/// whole file.
+#[derive(PartialEq, Debug)]
+pub struct Ed2kHash {
+    pub digest: [u8; 16],
+    pub size: u64,
+}
+
+impl Ed2kHash {
+    pub fn new(digest: [u8; 16], size: u64) -> Self {
+        Ed2kHash { digest, size }
+    }
+
+    pub fn as_hex(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns an `ed2k://|file|name|size|hash|/` link for the file.
+    pub fn link(&self, name: &str) -> String {
+        let mut escaped = String::with_capacity(name.len());
+
+        for c in name.chars() {
+            match c {
+                '|' | '%' | '/' => escaped.push_str(&format!("%{:02X}", c as u32)),
+                c if c.is_control() => escaped.push_str(&format!("%{:02X}", c as u32)),
+                c => escaped.push(c),
+            }
+        }
+
+        format!("ed2k://|file|{}|{}|{}|/", escaped, self.size, self)
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_path_with_progress(path, Ed2kVariant::default(), |_| {})
+    }
+
+    /// Computes the hash of the file at `path`, calling `progress` with the
+    /// number of bytes hashed so far as the file is read.
+    pub fn from_path_with_progress<P, F>(
+        path: P,
+        variant: Ed2kVariant,
+        progress: F,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64),
+    {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from(err).with_path(path))?;
+
+        Self::from_reader_with_progress(file, variant, progress).map_err(|err| err.with_path(path))
+    }
+
+    /// Computes the hash of an already opened file without reopening it.
+    pub fn from_file(mut file: &File) -> Result<Self, Error> {
+        file.seek(SeekFrom::Start(0)).map_err(Error::from)?;
+
+        Self::from_reader(file)
+    }
+
+    /// Computes the hash of everything read from `reader`.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_reader_with_progress(reader, Ed2kVariant::default(), |_| {})
+    }
+
+    /// Computes the hash of everything read from `reader`, calling `progress`
+    /// with the number of bytes hashed so far after every read.
+    pub fn from_reader_with_progress<R, F>(
+        mut reader: R,
+        variant: Ed2kVariant,
+        mut progress: F,
+    ) -> Result<Self, Error>
+    where
+        R: Read,
+        F: FnMut(u64),
+    {
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut state = Ed2kState::default();
+
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::from(err)),
+            };
+
+            state.update(&buffer[..read]);
+            progress(state.size);
+        }
+
+        Ok(state.finalize(variant))
+    }
+}
+
+/// Incremental state of an [`Ed2kHash`], fed with the whole file in order.
+#[derive(Default)]
+pub(crate) struct Ed2kState {
+    block: Md4,
+    block_len: u64,
+    digests: Vec<[u8; 16]>,
+    size: u64,
+}
+
+impl Ed2kState {
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        self.size += data.len() as u64;
+
+        while !data.is_empty() {
+            let len = data.len().min((ED2K_BLOCK_SIZE - self.block_len) as usize);
+            self.block.update(&data[..len]);
+            self.block_len += len as u64;
+            data = &data[len..];
+
+            if self.block_len == ED2K_BLOCK_SIZE {
+                self.digests.push(self.block.finalize_reset().into());
+                self.block_len = 0;
+            }
+        }
+    }
+
+    pub(crate) fn finalize(mut self, variant: Ed2kVariant) -> Ed2kHash {
+        if self.block_len > 0 || self.size == 0 || variant == Ed2kVariant::Red {
+            self.digests.push(self.block.finalize().into());
+        }
+
+        let digest = match self.digests.as_slice() {
+            [digest] => *digest,
+            digests => Md4::digest(digests.concat()).into(),
+        };
+
+        Ed2kHash::new(digest, self.size)
+    }
+}
+
+impl Display for Ed2kHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for byte in self.digest {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zeros(len: u64) -> impl Read {
+        io::repeat(0).take(len)
+    }
+
+    #[test]
+    fn should_return_valid_hash() {
+        let hash = Ed2kHash::from_path("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(hash.as_hex(), "b944f95c6c282d80013e4a4cbb7fa78c");
+        assert_eq!(hash.size, 12909756);
+        assert_eq!(
+            Ed2kHash::from_path("test-files/small.txt")
+                .unwrap()
+                .as_hex(),
+            "ba181efa6bf8e3fbdc25c9c92fb9763e"
+        );
+        assert_eq!(
+            Ed2kHash::from_reader(io::empty()).unwrap().as_hex(),
+            "31d6cfe0d16ae931b73c59d7e0c089c0"
+        );
+    }
+
+    #[test]
+    fn should_differ_between_variants_on_block_boundaries() {
+        let hash = |len, variant| {
+            Ed2kHash::from_reader_with_progress(zeros(len), variant, |_| {})
+                .unwrap()
+                .as_hex()
+        };
+
+        assert_eq!(
+            hash(ED2K_BLOCK_SIZE, Ed2kVariant::Blue),
+            "d7def262a127cd79096a108e7a9fc138"
+        );
+        assert_eq!(
+            hash(ED2K_BLOCK_SIZE, Ed2kVariant::Red),
+            "fc21d9af828f92a8df64beac3357425d"
+        );
+        assert_eq!(
+            hash(ED2K_BLOCK_SIZE * 2, Ed2kVariant::Blue),
+            "194ee9e4fa79b2ee9f8829284c466051"
+        );
+        assert_eq!(
+            hash(ED2K_BLOCK_SIZE * 2, Ed2kVariant::Red),
+            "114b21c63a74b6ca922291a11177dd5c"
+        );
+        assert_eq!(hash(0, Ed2kVariant::Red), hash(0, Ed2kVariant::Blue));
+    }
+
+    #[test]
+    fn should_report_progress() {
+        let mut reported = Vec::new();
+        Ed2kHash::from_path_with_progress("test-files/breakdance.avi", Ed2kVariant::Blue, |done| {
+            reported.push(done)
+        })
+        .unwrap();
+
+        assert!(reported.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reported.last(), Some(&12909756));
+    }
+
+    #[test]
+    fn should_format_link() {
+        let hash = Ed2kHash::from_path("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            hash.link("break|dance 100%.avi"),
+            "ed2k://|file|break%7Cdance 100%25.avi|12909756|b944f95c6c282d80013e4a4cbb7fa78c|/"
+        );
+    }
+
+    #[test]
+    fn should_return_errors_with_path() {
+        let err = Ed2kHash::from_path("test-files/non-existing.mp4").unwrap_err();
+
+        assert_eq!(err.path(), Some(Path::new("test-files/non-existing.mp4")));
+    }
+}
@@ -0,0 +1,128 @@
+use embedded_io::{Read, ReadExactError, Seek, SeekFrom};
+
+use crate::{Accumulator, CHUNK_SIZE, MovieHash, PartsError};
+
+/// Size of the stack buffer the head and tail are read through.
+const BUFFER_SIZE: usize = 512;
+
+impl MovieHash {
+    /// Computes the hash of an [`embedded_io`] source, determining its size by
+    /// seeking to the end. Neither `std` nor an allocator is required.
+    pub fn from_embedded_reader<R: Read + Seek>(
+        reader: &mut R,
+    ) -> Result<Self, PartsError<R::Error>> {
+        let size = reader.seek(SeekFrom::End(0)).map_err(PartsError::Io)?;
+
+        Self::from_embedded_reader_with_size(reader, size)
+    }
+
+    /// Computes the hash of an [`embedded_io`] source whose total length is
+    /// already known.
+    pub fn from_embedded_reader_with_size<R: Read + Seek>(
+        reader: &mut R,
+        size: u64,
+    ) -> Result<Self, PartsError<R::Error>> {
+        let mut accumulator = Accumulator::new(size).map_err(with_io)?;
+        let mut buffer = [0u8; BUFFER_SIZE];
+
+        for offset in [0, size - CHUNK_SIZE] {
+            reader
+                .seek(SeekFrom::Start(offset))
+                .map_err(PartsError::Io)?;
+
+            for _ in 0..CHUNK_SIZE as usize / BUFFER_SIZE {
+                reader.read_exact(&mut buffer).map_err(|err| match err {
+                    ReadExactError::UnexpectedEof => PartsError::UnexpectedEof,
+                    ReadExactError::Other(err) => PartsError::Io(err),
+                })?;
+                accumulator.update(&buffer);
+            }
+        }
+
+        accumulator.finish().map_err(with_io)
+    }
+}
+
+/// Widens an error of the reader-less API to one with a reader error type.
+fn with_io<E>(err: PartsError) -> PartsError<E> {
+    match err {
+        PartsError::SmallSize { size, min } => PartsError::SmallSize { size, min },
+        PartsError::Length { expected, actual } => PartsError::Length { expected, actual },
+        PartsError::UnexpectedEof => PartsError::UnexpectedEof,
+        PartsError::Io(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_io::ErrorType;
+
+    use super::*;
+
+    /// A seekable in-memory source, as `embedded_io` provides none.
+    struct Slice<'a> {
+        bytes: &'a [u8],
+        position: usize,
+    }
+
+    impl ErrorType for Slice<'_> {
+        type Error = Infallible;
+    }
+
+    impl Read for Slice<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let mut rest = &self.bytes[self.position.min(self.bytes.len())..];
+            let read = rest.read(buf)?;
+            self.position += read;
+            Ok(read)
+        }
+    }
+
+    impl Seek for Slice<'_> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error> {
+            self.position = match pos {
+                SeekFrom::Start(offset) => offset as usize,
+                SeekFrom::End(offset) => (self.bytes.len() as i64 + offset) as usize,
+                SeekFrom::Current(offset) => (self.position as i64 + offset) as usize,
+            };
+            Ok(self.position as u64)
+        }
+    }
+
+    #[test]
+    fn should_return_same_hash_as_std_reader() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+        let mut reader = Slice {
+            bytes: &bytes,
+            position: 0,
+        };
+
+        assert_eq!(
+            MovieHash::from_embedded_reader(&mut reader).unwrap(),
+            MovieHash(0x8e245d9679d31e12)
+        );
+    }
+
+    #[test]
+    fn should_return_errors() {
+        let bytes = [0u8; 100];
+        let mut reader = Slice {
+            bytes: &bytes,
+            position: 0,
+        };
+
+        assert_eq!(
+            MovieHash::from_embedded_reader(&mut reader),
+            Err(PartsError::SmallSize {
+                size: 100,
+                min: CHUNK_SIZE
+            })
+        );
+        assert_eq!(
+            MovieHash::from_embedded_reader_with_size(&mut reader, CHUNK_SIZE),
+            Err(PartsError::UnexpectedEof)
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt::{Debug, Display};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{CHUNK_SIZE, Error, MovieHash, add_words, read_chunk};
+
+/// A fingerprint computed from a few byte ranges of a seekable source.
+///
+/// Implementors only describe which ranges they need and how to combine them;
+/// reading, size checks and error paths are provided.
+pub trait FileFingerprint: Sized + PartialEq + Debug + Display {
+    /// Short lowercase name of the algorithm, used to tell cache files apart.
+    const NAME: &'static str;
+
+    /// Smallest source size in bytes the fingerprint can be computed for.
+    const MIN_SIZE: u64;
+
+    /// Byte ranges read from a source of `size` bytes, which is at least
+    /// [`Self::MIN_SIZE`]. Ranges may overlap and need not be sorted.
+    fn ranges(size: u64) -> Vec<Range<u64>>;
+
+    /// Computes the fingerprint of a source of `size` bytes from the contents
+    /// of its [`Self::ranges`], given in the same order.
+    fn from_ranges(size: u64, chunks: &[&[u8]]) -> Self;
+
+    /// Parses the output of [`Self::to_hex`].
+    fn from_hex(hex: &str) -> Option<Self>;
+
+    fn to_hex(&self) -> String {
+        self.to_string()
+    }
+
+    fn compute_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from(err).with_path(path))?;
+
+        Self::compute_file(&file).map_err(|err| err.with_path(path))
+    }
+
+    fn compute_file(file: &File) -> Result<Self, Error> {
+        let size = file.metadata().map_err(Error::from)?.len();
+
+        Self::compute_reader_with_size(file, size)
+    }
+
+    fn compute_reader<R: Read + Seek>(mut reader: R) -> Result<Self, Error> {
+        let size = reader.seek(SeekFrom::End(0)).map_err(Error::from)?;
+
+        Self::compute_reader_with_size(reader, size)
+    }
+
+    /// Computes the fingerprint of a forward-only source in a single pass.
+    ///
+    /// Fingerprints whose ranges depend on the size cannot be computed before
+    /// the end is reached, so by default this fails with
+    /// [`io::ErrorKind::Unsupported`].
+    fn compute_stream<R: Read>(_reader: R) -> Result<Self, Error> {
+        Err(Error::from(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} hashes cannot be computed from a stream", Self::NAME),
+        )))
+    }
+
+    fn compute_reader_with_size<R: Read + Seek>(mut reader: R, size: u64) -> Result<Self, Error> {
+        if size < Self::MIN_SIZE {
+            return Err(Error::SmallSize {
+                path: None,
+                size,
+                min: Self::MIN_SIZE,
+            });
+        }
+
+        let mut chunks = Vec::new();
+
+        for range in Self::ranges(size) {
+            let mut chunk = vec![0; (range.end - range.start) as usize];
+            read_chunk(&mut reader, range.start, &mut chunk)?;
+            chunks.push(chunk);
+        }
+
+        let chunks: Vec<&[u8]> = chunks.iter().map(Vec::as_slice).collect();
+
+        Ok(Self::from_ranges(size, &chunks))
+    }
+}
+
+impl FileFingerprint for MovieHash {
+    const NAME: &'static str = "opensubtitles";
+    const MIN_SIZE: u64 = CHUNK_SIZE;
+
+    fn ranges(size: u64) -> Vec<Range<u64>> {
+        vec![0..CHUNK_SIZE, size - CHUNK_SIZE..size]
+    }
+
+    fn from_ranges(size: u64, chunks: &[&[u8]]) -> Self {
+        MovieHash::new(add_words(add_words(size, chunks[0]), chunks[1]))
+    }
+
+    fn compute_file(file: &File) -> Result<Self, Error> {
+        Self::from_file(file)
+    }
+
+    fn compute_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_stream(reader)
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        hex.parse().ok()
+    }
+}
+
+/// Decodes exactly `N` bytes written as lowercase or uppercase hex digits.
+pub(crate) fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut bytes = [0; N];
+
+    for (byte, digits) in bytes.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        let digits = std::str::from_utf8(digits).ok()?;
+
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        *byte = u8::from_str_radix(digits, 16).ok()?;
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{NapiHash, ShooterHash, SubDbHash};
+
+    /// Computes `F` through the trait and checks it round trips through hex.
+    fn fingerprint<F: FileFingerprint>(path: &str) -> F {
+        let bytes = std::fs::read(path).unwrap();
+        let hash = F::compute_reader(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(
+            F::from_hex(&hash.to_hex()),
+            Some(F::compute_path(path).unwrap())
+        );
+        assert_eq!(
+            F::from_hex(&hash.to_hex().to_uppercase()).as_ref(),
+            Some(&hash)
+        );
+
+        hash
+    }
+
+    #[test]
+    fn should_match_inherent_methods() {
+        let path = "test-files/breakdance.avi";
+
+        assert_eq!(
+            fingerprint::<MovieHash>(path),
+            MovieHash::from_path(path).unwrap()
+        );
+        assert_eq!(
+            fingerprint::<SubDbHash>(path),
+            SubDbHash::from_path(path).unwrap()
+        );
+        assert_eq!(
+            fingerprint::<NapiHash>(path),
+            NapiHash::from_path(path).unwrap()
+        );
+        assert_eq!(
+            fingerprint::<ShooterHash>(path),
+            ShooterHash::from_path(path).unwrap()
+        );
+        assert_eq!(
+            fingerprint::<NapiHash>("test-files/small.txt"),
+            NapiHash::from_path("test-files/small.txt").unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_leave_file_cursor_alone_for_movie_hash() {
+        let mut file = File::open("test-files/breakdance.avi").unwrap();
+        file.seek(SeekFrom::Start(1000)).unwrap();
+
+        assert_eq!(
+            MovieHash::compute_file(&file).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+        assert_eq!(file.stream_position().unwrap(), 1000);
+    }
+
+    #[test]
+    fn should_reject_invalid_hex() {
+        for hex in [
+            "",
+            "8e245d9679d31e1",
+            "8e245d9679d31e12a",
+            "+e245d9679d31e12",
+            "8e245d9679d31e1g",
+        ] {
+            assert_eq!(MovieHash::from_hex(hex), None);
+        }
+
+        assert_eq!(SubDbHash::from_hex("559075d64f311fba4abc08a43b1eff7"), None);
+        assert_eq!(
+            SubDbHash::from_hex("559075d64f311fba4abc08a43b1eff7é"),
+            None
+        );
+        assert_eq!(
+            ShooterHash::from_hex("559075d64f311fba4abc08a43b1eff7e"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_compute_stream_only_when_supported() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            SubDbHash::compute_stream(&bytes[..]).unwrap(),
+            SubDbHash::from_path("test-files/breakdance.avi").unwrap()
+        );
+        assert_eq!(
+            NapiHash::compute_stream(&bytes[..]).unwrap(),
+            NapiHash::from_path("test-files/breakdance.avi").unwrap()
+        );
+        assert!(matches!(
+            ShooterHash::compute_stream(&bytes[..]),
+            Err(Error::Io { source, .. }) if source.kind() == io::ErrorKind::Unsupported
+        ));
+    }
+
+    #[test]
+    fn should_return_small_size_error_with_path() {
+        let err = ShooterHash::compute_path("test-files/small.txt").unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::SmallSize { size: 20, min: 12288, ref path } if path.as_deref() == Some(Path::new("test-files/small.txt"))
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::{CHUNK_SIZE, Error, MovieHash, add_words, read_chunk};
+
+/// How [`MovieHasher`] hashes files smaller than [`CHUNK_SIZE`], which have
+/// no distinct head and tail. Larger files hash the same under every policy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SmallFilePolicy {
+    /// Fails with [`Error::SmallSize`], as [`MovieHash::from_path`] does.
+    #[default]
+    Reject,
+    /// Zero-pads the file to [`CHUNK_SIZE`] bytes for both reads. The head is
+    /// padded after the file and the tail before it, so the words of the
+    /// tail stay aligned to the end of the file.
+    ///
+    /// The 20 byte `File with small size` hashes to `cd2fd6a3cd2fd6b6`.
+    ZeroPad,
+    /// Uses the whole file as both the head and the tail, zero-padding its
+    /// last partial word.
+    ///
+    /// The 20 byte `File with small size` hashes to `29abc70470b3e656`.
+    Overlap,
+}
+
+/// Computes [`MovieHash`]es with options the plain constructors do not take.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MovieHasher {
+    small_file_policy: SmallFilePolicy,
+}
+
+impl MovieHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how files smaller than [`CHUNK_SIZE`] are hashed.
+    pub fn small_file_policy(mut self, policy: SmallFilePolicy) -> Self {
+        self.small_file_policy = policy;
+        self
+    }
+
+    pub fn hash_path<P: AsRef<Path>>(&self, path: P) -> Result<MovieHash, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from(err).with_path(path))?;
+
+        self.hash_file(&file).map_err(|err| err.with_path(path))
+    }
+
+    /// Hashes an already opened file without reopening it.
+    pub fn hash_file(&self, file: &File) -> Result<MovieHash, Error> {
+        let size = file.metadata().map_err(Error::from)?.len();
+
+        self.hash_reader_with_size(file, size)
+    }
+
+    /// Hashes any seekable source, determining its size by seeking to the end.
+    pub fn hash_reader<R: Read + Seek>(&self, mut reader: R) -> Result<MovieHash, Error> {
+        let size = reader.seek(SeekFrom::End(0)).map_err(Error::from)?;
+
+        self.hash_reader_with_size(reader, size)
+    }
+
+    /// Hashes a seekable source whose total length is already known.
+    pub fn hash_reader_with_size<R: Read + Seek>(
+        &self,
+        mut reader: R,
+        size: u64,
+    ) -> Result<MovieHash, Error> {
+        if size >= CHUNK_SIZE || self.small_file_policy == SmallFilePolicy::Reject {
+            return MovieHash::from_reader_with_size(reader, size);
+        }
+
+        let mut data = vec![0u8; size as usize];
+        read_chunk(&mut reader, 0, &mut data)?;
+
+        let hash = if self.small_file_policy == SmallFilePolicy::ZeroPad {
+            let padding = vec![0u8; (CHUNK_SIZE - size) as usize];
+            let head = [&data[..], &padding].concat();
+            let tail = [&padding[..], &data].concat();
+
+            add_words(add_words(size, &head), &tail)
+        } else {
+            data.resize(data.len().next_multiple_of(8), 0);
+
+            add_words(add_words(size, &data), &data)
+        };
+
+        Ok(MovieHash::new(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    const POLICIES: [SmallFilePolicy; 3] = [
+        SmallFilePolicy::Reject,
+        SmallFilePolicy::ZeroPad,
+        SmallFilePolicy::Overlap,
+    ];
+
+    #[test]
+    fn should_hash_small_file_per_policy() {
+        let hasher = MovieHasher::new();
+
+        assert!(matches!(
+            hasher.hash_path("test-files/small.txt"),
+            Err(Error::SmallSize {
+                path: Some(_),
+                size: 20,
+                min: CHUNK_SIZE
+            })
+        ));
+        assert_eq!(
+            hasher
+                .small_file_policy(SmallFilePolicy::ZeroPad)
+                .hash_path("test-files/small.txt")
+                .unwrap()
+                .as_hex(),
+            "cd2fd6a3cd2fd6b6"
+        );
+        assert_eq!(
+            hasher
+                .small_file_policy(SmallFilePolicy::Overlap)
+                .hash_path("test-files/small.txt")
+                .unwrap()
+                .as_hex(),
+            "29abc70470b3e656"
+        );
+    }
+
+    #[test]
+    fn should_hash_empty_file_as_zero() {
+        for policy in [SmallFilePolicy::ZeroPad, SmallFilePolicy::Overlap] {
+            assert_eq!(
+                MovieHasher::new()
+                    .small_file_policy(policy)
+                    .hash_reader(io::empty())
+                    .unwrap(),
+                MovieHash::new(0)
+            );
+        }
+    }
+
+    #[test]
+    fn should_ignore_policy_for_large_files() {
+        for policy in POLICIES {
+            assert_eq!(
+                MovieHasher::new()
+                    .small_file_policy(policy)
+                    .hash_path("test-files/breakdance.avi")
+                    .unwrap()
+                    .as_hex(),
+                "8e245d9679d31e12"
+            );
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::fmt::Display;
+use std::io::{self, Read};
+
+use ureq::Agent;
+use ureq::http::{Response, StatusCode};
+
+use crate::{CHUNK_SIZE, Error, MovieHash, add_words};
+
+/// Reasons a remote file could not be hashed. They are reported as the
+/// source of an [`Error::Io`] whose path is the requested URL.
+#[derive(Debug)]
+pub enum HttpError {
+    Request(ureq::Error),
+    Status(u16),
+    RangesIgnored,
+    UnknownLength,
+    UnexpectedRange {
+        requested: String,
+        content_range: Option<String>,
+    },
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Request(err) => write!(f, "{}", err),
+            Self::Status(status) => write!(f, "server responded with status {}", status),
+            Self::RangesIgnored => write!(f, "server does not support Range requests"),
+            Self::UnknownLength => write!(f, "server did not report the content length"),
+            Self::UnexpectedRange {
+                requested,
+                content_range: Some(content_range),
+            } => write!(
+                f,
+                "requested {} but server sent Content-Range {}",
+                requested, content_range
+            ),
+            Self::UnexpectedRange {
+                requested,
+                content_range: None,
+            } => write!(
+                f,
+                "requested {} but server sent no Content-Range",
+                requested
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<HttpError> for io::Error {
+    fn from(value: HttpError) -> Self {
+        let kind = match value {
+            HttpError::RangesIgnored => io::ErrorKind::Unsupported,
+            HttpError::Request(ureq::Error::Io(ref err)) => err.kind(),
+            _ => io::ErrorKind::Other,
+        };
+
+        io::Error::new(kind, value)
+    }
+}
+
+impl From<ureq::Error> for HttpError {
+    fn from(value: ureq::Error) -> Self {
+        Self::Request(value)
+    }
+}
+
+impl MovieHash {
+    /// Computes the hash of a remote file with two `Range` requests for its
+    /// first and last 64 KiB.
+    ///
+    /// The file size is taken from the `Content-Range` of the first response,
+    /// falling back to the `Content-Length` of a `HEAD` request.
+    pub fn from_url(url: &str) -> Result<Self, Error> {
+        let agent: Agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+
+        Self::from_url_with_agent(&agent, url)
+    }
+
+    /// Same as [`MovieHash::from_url`] but sends the requests through `agent`,
+    /// which must be configured with `http_status_as_error(false)`.
+    pub fn from_url_with_agent(agent: &Agent, url: &str) -> Result<Self, Error> {
+        let to_error = |err: HttpError| Error::from(io::Error::from(err)).with_path(url);
+
+        let (head, size) = get_range(agent, url, 0).map_err(to_error)?;
+        let size = match size {
+            Some(size) => size,
+            None => content_length(agent, url).map_err(to_error)?,
+        };
+
+        if size < CHUNK_SIZE {
+            return Err(Error::SmallSize {
+                path: Some(url.into()),
+                size,
+                min: CHUNK_SIZE,
+            });
+        }
+
+        let (tail, _) = get_range(agent, url, size - CHUNK_SIZE).map_err(to_error)?;
+
+        for chunk in [&head, &tail] {
+            if chunk.len() as u64 != CHUNK_SIZE {
+                return Err(
+                    Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)).with_path(url)
+                );
+            }
+        }
+
+        Ok(MovieHash::new(add_words(add_words(size, &head), &tail)))
+    }
+}
+
+/// Fetches up to `CHUNK_SIZE` bytes starting at `start`, returning them along
+/// with the total length reported in `Content-Range`, if any.
+fn get_range(agent: &Agent, url: &str, start: u64) -> Result<(Vec<u8>, Option<u64>), HttpError> {
+    let requested = format!("bytes={}-{}", start, start + CHUNK_SIZE - 1);
+    let mut response = agent.get(url).header("Range", &requested).call()?;
+
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {}
+        // Sent for empty files, where even the first byte is out of range.
+        StatusCode::RANGE_NOT_SATISFIABLE if start == 0 => {
+            return match content_range(&response) {
+                Some((_, Some(0))) => Ok((Vec::new(), Some(0))),
+                _ => Err(HttpError::Status(416)),
+            };
+        }
+        StatusCode::OK => return Err(HttpError::RangesIgnored),
+        status => return Err(HttpError::Status(status.as_u16())),
+    }
+
+    let total = match content_range(&response) {
+        Some((first, total)) if first == start => total,
+        _ => {
+            return Err(HttpError::UnexpectedRange {
+                requested,
+                content_range: header(&response, "content-range").map(String::from),
+            });
+        }
+    };
+
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE as usize);
+    response
+        .body_mut()
+        .as_reader()
+        .take(CHUNK_SIZE)
+        .read_to_end(&mut chunk)
+        .map_err(ureq::Error::from)?;
+
+    Ok((chunk, total))
+}
+
+fn content_length(agent: &Agent, url: &str) -> Result<u64, HttpError> {
+    let response = agent.head(url).call()?;
+
+    if !response.status().is_success() {
+        return Err(HttpError::Status(response.status().as_u16()));
+    }
+
+    header(&response, "content-length")
+        .and_then(|length| length.trim().parse().ok())
+        .ok_or(HttpError::UnknownLength)
+}
+
+/// Parses `bytes <first>-<last>/<total>` or `bytes */<total>` into the first
+/// byte and, unless it is `*`, the total length.
+fn content_range<B>(response: &Response<B>) -> Option<(u64, Option<u64>)> {
+    let value = header(response, "content-range")?.trim();
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    let first = match range {
+        "*" => 0,
+        range => range.split_once('-')?.0.parse().ok()?,
+    };
+
+    Some((first, total))
+}
+
+fn header<'a, B>(response: &'a Response<B>, name: &str) -> Option<&'a str> {
+    response.headers().get(name)?.to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::test_util::movie;
+
+    #[derive(Clone, Copy)]
+    enum Server {
+        Ranges,
+        RangesWithoutTotal,
+        IgnoresRanges,
+    }
+
+    /// Serves `bytes` at `http://127.0.0.1:<port>/` until the test exits.
+    fn serve(bytes: Vec<u8>, server: Server) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/movie.avi", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                let mut range = None;
+                reader.read_line(&mut request_line).unwrap();
+
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("range: bytes=") {
+                        let (first, last) = value.trim().split_once('-').unwrap();
+                        range = Some((
+                            first.parse::<usize>().unwrap(),
+                            last.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+
+                let len = bytes.len();
+                let (status, headers, body) = match (range, server) {
+                    (Some((first, last)), Server::Ranges | Server::RangesWithoutTotal) => {
+                        let last = last.min(len - 1);
+                        let total = match server {
+                            Server::Ranges => len.to_string(),
+                            _ => "*".to_string(),
+                        };
+
+                        (
+                            "206 Partial Content",
+                            format!("Content-Range: bytes {}-{}/{}\r\n", first, last, total),
+                            &bytes[first..=last],
+                        )
+                    }
+                    _ => ("200 OK", String::new(), &bytes[..]),
+                };
+                let body = if request_line.starts_with("HEAD") {
+                    &[][..]
+                } else {
+                    body
+                };
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    headers,
+                    if request_line.starts_with("HEAD") {
+                        len
+                    } else {
+                        body.len()
+                    }
+                );
+                let _ = stream.write_all(body);
+            }
+        });
+
+        url
+    }
+
+    #[test]
+    fn should_return_same_hash_as_local_file() {
+        let url = serve(movie(), Server::Ranges);
+
+        assert_eq!(
+            MovieHash::from_url(&url).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_content_length_of_head_request() {
+        let url = serve(movie(), Server::RangesWithoutTotal);
+
+        assert_eq!(
+            MovieHash::from_url(&url).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_return_error_when_server_ignores_ranges() {
+        let url = serve(movie(), Server::IgnoresRanges);
+        let err = MovieHash::from_url(&url).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!("{}: server does not support Range requests", url)
+        );
+        assert!(matches!(
+            err,
+            Error::Io { source, .. } if source.kind() == io::ErrorKind::Unsupported
+        ));
+    }
+
+    #[test]
+    fn should_return_small_size_error() {
+        let url = serve(vec![1; 20], Server::Ranges);
+
+        assert!(matches!(
+            MovieHash::from_url(&url),
+            Err(Error::SmallSize {
+                size: 20,
+                min: CHUNK_SIZE,
+                ..
+            })
+        ));
+    }
+}
@@ -1,100 +1,299 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(feature = "std")]
 use core::error;
-use std::fmt::Display;
+use core::fmt::Display;
+#[cfg(feature = "std")]
 use std::fs::File;
-use std::io::{self, BufReader, Read, Seek, SeekFrom};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "std")]
+pub mod archive;
+#[cfg(feature = "async")]
+mod async_io;
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "std")]
+mod ed2k;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+#[cfg(feature = "std")]
+pub mod fingerprint;
+#[cfg(feature = "std")]
+mod hasher;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "std")]
+pub mod multi;
+#[cfg(feature = "std")]
+mod napi;
+mod parse;
+mod parts;
+#[cfg(feature = "std")]
+mod pread;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "std")]
+mod shooter;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+mod subdb;
+#[cfg(all(test, feature = "std"))]
+mod test_util;
+#[cfg(feature = "std")]
+pub mod walker;
+#[cfg(feature = "wasm")]
+mod wasm;
 
-#[derive(PartialEq, Debug)]
+#[cfg(feature = "std")]
+pub use ed2k::{Ed2kHash, Ed2kVariant};
+#[cfg(feature = "std")]
+pub use fingerprint::FileFingerprint;
+#[cfg(feature = "std")]
+pub use hasher::{MovieHasher, SmallFilePolicy};
+#[cfg(feature = "std")]
+pub use multi::{Digests, MultiHasher};
+#[cfg(feature = "std")]
+pub use napi::NapiHash;
+pub use parse::ParseError;
+pub use parts::{Accumulator, PartsError};
+#[cfg(feature = "std")]
+pub use shooter::ShooterHash;
+#[cfg(feature = "std")]
+pub use subdb::SubDbHash;
+#[cfg(feature = "std")]
+pub use walker::{HashWalker, hash_dir};
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
 pub enum Error {
-    SmallSize,
-    Io(io::ErrorKind),
+    SmallSize {
+        path: Option<PathBuf>,
+        size: u64,
+        min: u64,
+    },
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Returns the path of the file that caused the error, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::SmallSize { path, .. } | Self::Io { path, .. } => path.as_deref(),
+        }
+    }
+
+    /// Attaches `path` to the error unless it already refers to a path.
+    pub fn with_path<P: AsRef<Path>>(mut self, new_path: P) -> Self {
+        match &mut self {
+            Self::SmallSize { path, .. } | Self::Io { path, .. } => {
+                path.get_or_insert_with(|| new_path.as_ref().to_path_buf());
+            }
+        }
+        self
+    }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(value: io::Error) -> Self {
-        Self::Io(value.kind())
+        Self::Io {
+            path: None,
+            source: value,
+        }
     }
 }
 
+#[cfg(feature = "std")]
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "{}: ", path.display())?;
+        }
+
         match self {
-            Self::SmallSize => write!(f, "file size is less than 64 KB"),
-            Self::Io(err_kind) => write!(f, "{}", err_kind),
+            Self::SmallSize { size, min, .. } => {
+                write!(f, "file size of {} bytes is less than {} bytes", size, min)
+            }
+            Self::Io { source, .. } => write!(f, "{}", source),
         }
     }
 }
 
-impl error::Error for Error {}
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::SmallSize { .. } => None,
+            Self::Io { source, .. } => Some(source),
+        }
+    }
+}
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 pub struct MovieHash(pub u64);
 
-const CHUNK_SIZE: u64 = 65536;
+/// Number of bytes hashed at each end of a file.
+pub const CHUNK_SIZE: u64 = 65536;
+
+/// Extensions of the files [`HashWalker`] and [`archive::Archive::video`]
+/// consider to be videos.
+pub const VIDEO_EXTENSIONS: &[&str] = &[
+    "3g2", "3gp", "asf", "avi", "divx", "flv", "m2ts", "m4v", "mkv", "mov", "mp4", "mpeg", "mpg",
+    "mts", "ogm", "ogv", "rm", "rmvb", "ts", "vob", "webm", "wmv",
+];
+
+#[cfg(feature = "std")]
+/// Returns whether `path` has one of the common video file extensions.
+fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            VIDEO_EXTENSIONS
+                .iter()
+                .any(|video| extension.eq_ignore_ascii_case(video))
+        })
+}
+
+#[cfg(feature = "std")]
+/// Reads the first and last `CHUNK_SIZE` bytes of a source of `size` bytes,
+/// the ranges every head-and-tail hash is computed from.
+fn read_head_and_tail<R: Read + Seek>(
+    mut reader: R,
+    size: u64,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    if size < CHUNK_SIZE {
+        return Err(Error::SmallSize {
+            path: None,
+            size,
+            min: CHUNK_SIZE,
+        });
+    }
+
+    let mut head = vec![0u8; CHUNK_SIZE as usize];
+    let mut tail = vec![0u8; CHUNK_SIZE as usize];
+    read_chunk(&mut reader, 0, &mut head)?;
+    read_chunk(&mut reader, size - CHUNK_SIZE, &mut tail)?;
+
+    Ok((head, tail))
+}
+
+#[cfg(feature = "std")]
+/// Fills `buf` with the bytes of `reader` starting at `offset`.
+fn read_chunk<R: Read + Seek>(reader: &mut R, offset: u64, buf: &mut [u8]) -> Result<(), Error> {
+    reader.seek(SeekFrom::Start(offset)).map_err(Error::from)?;
+    reader.read_exact(buf).map_err(Error::from)
+}
+
+/// Adds every little-endian 64-bit word of `chunk` to `hash`.
+fn add_words(hash: u64, chunk: &[u8]) -> u64 {
+    chunk.chunks_exact(8).fold(hash, |hash, word| {
+        hash.wrapping_add(u64::from_le_bytes(word.try_into().unwrap()))
+    })
+}
 
 impl MovieHash {
     pub fn new(hash: u64) -> Self {
         MovieHash(hash)
     }
 
+    #[cfg(feature = "std")]
     pub fn as_hex(&self) -> String {
         format!("{:016x}", self.0)
     }
+}
 
-    pub fn from_path(path: &str) -> Result<Self, Error> {
-        let file = File::open(path).map_err(Error::from)?;
-        let file_size = file.metadata().map_err(Error::from)?.len();
+#[cfg(feature = "std")]
+impl MovieHash {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from(err).with_path(path))?;
 
-        if file_size < CHUNK_SIZE {
-            return Err(Error::SmallSize);
-        };
+        Self::from_file(&file).map_err(|err| err.with_path(path))
+    }
 
-        let mut hash: u64 = file_size;
-        let mut reader = BufReader::with_capacity(CHUNK_SIZE as usize, file);
-        let mut word_buffer = [0u8; 8];
-        let word_count = CHUNK_SIZE / 8;
+    /// Computes the hash of an already opened file without reopening it.
+    ///
+    /// The head and tail are read with one positional read each, so the file
+    /// cursor is not used and the same file can be hashed from several
+    /// threads at once.
+    pub fn from_file(file: &File) -> Result<Self, Error> {
+        let size = file.metadata().map_err(Error::from)?.len();
 
-        for _ in 0..word_count {
-            reader.read_exact(&mut word_buffer).map_err(Error::from)?;
-            hash = hash.wrapping_add(u64::from_le_bytes(word_buffer));
+        if size < CHUNK_SIZE {
+            return Err(Error::SmallSize {
+                path: None,
+                size,
+                min: CHUNK_SIZE,
+            });
         }
 
-        reader
-            .seek(SeekFrom::Start(file_size - CHUNK_SIZE))
-            .map_err(Error::from)?;
+        let mut buffer = [0u8; CHUNK_SIZE as usize];
+        pread::read_exact_at(file, &mut buffer, 0).map_err(Error::from)?;
+        let hash = add_words(size, &buffer);
+        pread::read_exact_at(file, &mut buffer, size - CHUNK_SIZE).map_err(Error::from)?;
 
-        for _ in 0..word_count {
-            reader.read_exact(&mut word_buffer).map_err(Error::from)?;
-            hash = hash.wrapping_add(u64::from_le_bytes(word_buffer));
-        }
+        Ok(Self::new(add_words(hash, &buffer)))
+    }
+
+    /// Computes the hash of any seekable source, determining its size by
+    /// seeking to the end.
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Self, Error> {
+        let size = reader.seek(SeekFrom::End(0)).map_err(Error::from)?;
+
+        Self::from_reader_with_size(reader, size)
+    }
+
+    /// Computes the hash of a seekable source whose total length is already known.
+    pub fn from_reader_with_size<R: Read + Seek>(reader: R, size: u64) -> Result<Self, Error> {
+        let (head, tail) = read_head_and_tail(reader, size)?;
 
-        Ok(MovieHash::new(hash))
+        Ok(Self::from_parts(size, &head, &tail)?)
     }
 }
 
 impl Display for MovieHash {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "{:016x}", self.0)
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
     #[test]
     fn should_return_not_found_error() {
-        assert_eq!(
-            MovieHash::from_path("test-files/non-existing.mp4"),
-            Err(Error::Io(io::ErrorKind::NotFound))
-        );
+        let err = MovieHash::from_path("test-files/non-existing.mp4").unwrap_err();
+
+        assert!(matches!(
+            &err,
+            Error::Io { path: Some(path), source }
+                if path == Path::new("test-files/non-existing.mp4")
+                    && source.kind() == io::ErrorKind::NotFound
+        ));
     }
 
     #[test]
     fn should_return_small_size_error() {
-        assert_eq!(
-            MovieHash::from_path("test-files/small.txt"),
-            Err(Error::SmallSize)
-        );
+        let err = MovieHash::from_path("test-files/small.txt").unwrap_err();
+
+        assert!(matches!(
+            &err,
+            Error::SmallSize { path: Some(path), size: 20, min: CHUNK_SIZE }
+                if path == Path::new("test-files/small.txt")
+        ));
     }
 
     #[test]
@@ -107,14 +306,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_return_same_hash_for_reader() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            MovieHash::from_reader(io::Cursor::new(&bytes)).unwrap(),
+            MovieHash::from_path("test-files/breakdance.avi").unwrap()
+        );
+        assert_eq!(
+            MovieHash::from_reader_with_size(io::Cursor::new(&bytes), bytes.len() as u64)
+                .unwrap()
+                .as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_return_small_size_error_for_reader() {
+        assert!(matches!(
+            MovieHash::from_reader(io::Cursor::new([0u8; 100])),
+            Err(Error::SmallSize {
+                path: None,
+                size: 100,
+                min: CHUNK_SIZE
+            })
+        ));
+    }
+
+    #[test]
+    fn should_return_same_hash_for_open_file() {
+        let mut file = File::open("test-files/breakdance.avi").unwrap();
+        file.seek(SeekFrom::Start(1000)).unwrap();
+
+        assert_eq!(
+            MovieHash::from_file(&file).unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+        #[cfg(unix)]
+        assert_eq!(file.stream_position().unwrap(), 1000);
+    }
+
+    #[test]
+    fn should_hash_shared_file_from_several_threads() {
+        let file = File::open("test-files/breakdance.avi").unwrap();
+
+        std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| MovieHash::from_file(&file).unwrap()))
+                .collect();
+
+            for thread in threads {
+                assert_eq!(thread.join().unwrap().as_hex(), "8e245d9679d31e12");
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_accept_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = test_util::temp_dir("non-utf8").join(OsStr::from_bytes(b"\xff\xfe.avi"));
+        std::fs::copy("test-files/breakdance.avi", &path).unwrap();
+        let result = MovieHash::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.unwrap().as_hex(), "8e245d9679d31e12");
+    }
+
     #[test]
     fn should_print_human_readable_error_messages() {
         let test_cases = [
-            (Error::SmallSize, "file size is less than 64 KB"),
-            (Error::Io(io::ErrorKind::NotFound), "entity not found"),
             (
-                Error::Io(io::ErrorKind::InvalidFilename),
-                "invalid filename",
+                Error::SmallSize {
+                    path: None,
+                    size: 20,
+                    min: CHUNK_SIZE,
+                },
+                "file size of 20 bytes is less than 65536 bytes",
+            ),
+            (
+                Error::SmallSize {
+                    path: Some("small.txt".into()),
+                    size: 20,
+                    min: CHUNK_SIZE,
+                },
+                "small.txt: file size of 20 bytes is less than 65536 bytes",
+            ),
+            (
+                Error::from(io::Error::from(io::ErrorKind::NotFound)),
+                "entity not found",
+            ),
+            (
+                Error::from(io::Error::from(io::ErrorKind::InvalidFilename)).with_path("a.mp4"),
+                "a.mp4: invalid filename",
             ),
         ];
 
@@ -122,4 +409,20 @@ mod tests {
             assert_eq!(format!("{}", err), message)
         }
     }
+
+    #[test]
+    fn should_expose_underlying_io_error_as_source() {
+        let err = Error::from(io::Error::other("disk on fire")).with_path("a.mp4");
+        let source = error::Error::source(&err).unwrap();
+
+        assert_eq!(source.to_string(), "disk on fire");
+        assert!(
+            error::Error::source(&Error::SmallSize {
+                path: None,
+                size: 0,
+                min: CHUNK_SIZE
+            })
+            .is_none()
+        );
+    }
 }
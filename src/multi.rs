@@ -0,0 +1,388 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::ed2k::Ed2kState;
+use crate::{
+    CHUNK_SIZE, Ed2kHash, Ed2kVariant, Error, FileFingerprint, MovieHash, NapiHash, ShooterHash,
+    SubDbHash, read_chunk,
+};
+
+/// Computes several hashes of the same file while reading every byte at most
+/// once.
+///
+/// The byte ranges needed by the selected algorithms are merged before
+/// reading, so for example the head and tail of [`MovieHash`] and
+/// [`SubDbHash`] are only read once. When [`Ed2kHash`] is selected the whole
+/// file is streamed in a single pass and the ranges are collected on the way.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MultiHasher {
+    movie_hash: bool,
+    subdb: bool,
+    napi: bool,
+    shooter: bool,
+    ed2k: Option<Ed2kVariant>,
+}
+
+/// The hashes computed by a [`MultiHasher`]. Algorithms that were not selected
+/// are `None`, and those the file is too small for hold their
+/// [`Error::SmallSize`].
+#[derive(Debug)]
+pub struct Digests {
+    pub size: u64,
+    pub movie_hash: Option<Result<MovieHash, Error>>,
+    pub subdb: Option<Result<SubDbHash, Error>>,
+    pub napi: Option<Result<NapiHash, Error>>,
+    pub shooter: Option<Result<ShooterHash, Error>>,
+    pub ed2k: Option<Ed2kHash>,
+}
+
+impl MultiHasher {
+    /// Creates a hasher with no algorithms selected.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a hasher with every algorithm selected, using the default
+    /// [`Ed2kVariant`].
+    pub fn all() -> Self {
+        Self::new()
+            .movie_hash()
+            .subdb()
+            .napi()
+            .shooter()
+            .ed2k(Ed2kVariant::default())
+    }
+
+    pub fn movie_hash(mut self) -> Self {
+        self.movie_hash = true;
+        self
+    }
+
+    pub fn subdb(mut self) -> Self {
+        self.subdb = true;
+        self
+    }
+
+    pub fn napi(mut self) -> Self {
+        self.napi = true;
+        self
+    }
+
+    pub fn shooter(mut self) -> Self {
+        self.shooter = true;
+        self
+    }
+
+    /// Also computes the [`Ed2kHash`], which requires reading the whole file.
+    pub fn ed2k(mut self, variant: Ed2kVariant) -> Self {
+        self.ed2k = Some(variant);
+        self
+    }
+
+    pub fn hash_path<P: AsRef<Path>>(&self, path: P) -> Result<Digests, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from(err).with_path(path))?;
+        let size = file
+            .metadata()
+            .map_err(|err| Error::from(err).with_path(path))?
+            .len();
+
+        self.hash(&file, size, Some(path))
+            .map_err(|err| err.with_path(path))
+    }
+
+    /// Hashes an already opened file without reopening it.
+    pub fn hash_file(&self, file: &File) -> Result<Digests, Error> {
+        let size = file.metadata().map_err(Error::from)?.len();
+
+        self.hash_reader_with_size(file, size)
+    }
+
+    /// Hashes any seekable source, determining its size by seeking to the end.
+    pub fn hash_reader<R: Read + Seek>(&self, mut reader: R) -> Result<Digests, Error> {
+        let size = reader.seek(SeekFrom::End(0)).map_err(Error::from)?;
+
+        self.hash_reader_with_size(reader, size)
+    }
+
+    /// Hashes a seekable source whose total length is already known.
+    pub fn hash_reader_with_size<R: Read + Seek>(
+        &self,
+        reader: R,
+        size: u64,
+    ) -> Result<Digests, Error> {
+        self.hash(reader, size, None)
+    }
+
+    fn hash<R: Read + Seek>(
+        &self,
+        mut reader: R,
+        size: u64,
+        path: Option<&Path>,
+    ) -> Result<Digests, Error> {
+        let mut chunks = Chunks::plan(self.ranges(size));
+
+        let ed2k = match self.ed2k {
+            Some(variant) => Some(chunks.stream(&mut reader, size)?.finalize(variant)),
+            None => {
+                chunks.read(&mut reader)?;
+                None
+            }
+        };
+
+        Ok(Digests {
+            size,
+            movie_hash: self.movie_hash.then(|| chunks.compute(size, path)),
+            subdb: self.subdb.then(|| chunks.compute(size, path)),
+            napi: self.napi.then(|| chunks.compute(size, path)),
+            shooter: self.shooter.then(|| chunks.compute(size, path)),
+            ed2k,
+        })
+    }
+
+    /// Byte ranges the selected algorithms need from a file of `size` bytes,
+    /// leaving out those of algorithms the file is too small for.
+    fn ranges(&self, size: u64) -> Vec<Range<u64>> {
+        fn ranges_of<F: FileFingerprint>(selected: bool, size: u64) -> Vec<Range<u64>> {
+            if selected && size >= F::MIN_SIZE {
+                F::ranges(size)
+            } else {
+                Vec::new()
+            }
+        }
+
+        [
+            ranges_of::<MovieHash>(self.movie_hash, size),
+            ranges_of::<SubDbHash>(self.subdb, size),
+            ranges_of::<NapiHash>(self.napi, size),
+            ranges_of::<ShooterHash>(self.shooter, size),
+        ]
+        .concat()
+    }
+}
+
+/// The merged byte ranges of a file along with their contents.
+#[derive(Debug)]
+struct Chunks(Vec<(Range<u64>, Vec<u8>)>);
+
+impl Chunks {
+    /// Sorts `ranges` and merges those that overlap or touch.
+    fn plan(mut ranges: Vec<Range<u64>>) -> Self {
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<u64>> = Vec::new();
+
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Chunks(
+            merged
+                .into_iter()
+                .map(|range| {
+                    let len = (range.end - range.start) as usize;
+                    (range, vec![0; len])
+                })
+                .collect(),
+        )
+    }
+
+    /// Reads every range with one seek and read each.
+    fn read<R: Read + Seek>(&mut self, reader: &mut R) -> Result<(), Error> {
+        for (range, buf) in &mut self.0 {
+            read_chunk(reader, range.start, buf)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the whole source from the start into an [`Ed2kState`], copying
+    /// the planned ranges out of it as they pass by.
+    fn stream<R: Read + Seek>(&mut self, reader: &mut R, size: u64) -> Result<Ed2kState, Error> {
+        reader.seek(SeekFrom::Start(0)).map_err(Error::from)?;
+
+        let mut state = Ed2kState::default();
+        let mut buffer = vec![0u8; CHUNK_SIZE as usize];
+        let mut position = 0;
+
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(Error::from(err)),
+            };
+            let data = &buffer[..read];
+            let end = position + read as u64;
+
+            for (range, buf) in &mut self.0 {
+                let start = range.start.max(position);
+                let stop = range.end.min(end);
+
+                if start < stop {
+                    buf[(start - range.start) as usize..(stop - range.start) as usize]
+                        .copy_from_slice(
+                            &data[(start - position) as usize..(stop - position) as usize],
+                        );
+                }
+            }
+
+            state.update(data);
+            position = end;
+        }
+
+        // The ranges were planned for `size` bytes and would be incomplete.
+        if position != size {
+            return Err(Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+
+        Ok(state)
+    }
+
+    /// Computes `F` from the planned ranges of a file of `size` bytes.
+    fn compute<F: FileFingerprint>(&self, size: u64, path: Option<&Path>) -> Result<F, Error> {
+        if size < F::MIN_SIZE {
+            return Err(Error::SmallSize {
+                path: path.map(Path::to_path_buf),
+                size,
+                min: F::MIN_SIZE,
+            });
+        }
+
+        let chunks: Vec<&[u8]> = F::ranges(size)
+            .into_iter()
+            .map(|range| self.get(range))
+            .collect();
+
+        Ok(F::from_ranges(size, &chunks))
+    }
+
+    /// Returns the bytes of `range`, which must lie within a planned range.
+    fn get(&self, range: Range<u64>) -> &[u8] {
+        let (chunk, buf) = self
+            .0
+            .iter()
+            .find(|(chunk, _)| chunk.start <= range.start && range.end <= chunk.end)
+            .expect("range was not planned");
+
+        &buf[(range.start - chunk.start) as usize..(range.end - chunk.start) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::shooter::SHOOTER_BLOCK_SIZE;
+
+    /// Counts the bytes read through it.
+    struct Counting<R> {
+        inner: R,
+        read: u64,
+    }
+
+    impl<R: Read> Read for Counting<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.read += read as u64;
+            Ok(read)
+        }
+    }
+
+    impl<R: Seek> Seek for Counting<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn should_return_same_hashes_as_each_algorithm() {
+        let path = "test-files/breakdance.avi";
+
+        for hasher in [
+            MultiHasher::all(),
+            MultiHasher::all().ed2k(Ed2kVariant::Red),
+        ] {
+            let digests = hasher.hash_path(path).unwrap();
+
+            assert_eq!(digests.size, 12909756);
+            assert_eq!(
+                digests.movie_hash.unwrap().unwrap(),
+                MovieHash::from_path(path).unwrap()
+            );
+            assert_eq!(
+                digests.subdb.unwrap().unwrap(),
+                SubDbHash::from_path(path).unwrap()
+            );
+            assert_eq!(
+                digests.napi.unwrap().unwrap(),
+                NapiHash::from_path(path).unwrap()
+            );
+            assert_eq!(
+                digests.shooter.unwrap().unwrap(),
+                ShooterHash::from_path(path).unwrap()
+            );
+            assert_eq!(digests.ed2k.unwrap(), Ed2kHash::from_path(path).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_read_shared_ranges_once() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+        let mut reader = Counting {
+            inner: Cursor::new(&bytes),
+            read: 0,
+        };
+
+        let digests = MultiHasher::new()
+            .movie_hash()
+            .subdb()
+            .shooter()
+            .hash_reader(&mut reader)
+            .unwrap();
+        assert!(digests.napi.is_none() && digests.ed2k.is_none());
+        assert_eq!(
+            digests.movie_hash.unwrap().unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+        // The head, the tail and the two shooter blocks in between.
+        assert_eq!(reader.read, CHUNK_SIZE * 2 + SHOOTER_BLOCK_SIZE * 2);
+
+        reader.read = 0;
+        MultiHasher::all().hash_reader(&mut reader).unwrap();
+        assert_eq!(reader.read, bytes.len() as u64);
+    }
+
+    #[test]
+    fn should_report_small_size_per_algorithm() {
+        let digests = MultiHasher::all()
+            .hash_path("test-files/small.txt")
+            .unwrap();
+
+        assert!(matches!(
+            digests.movie_hash,
+            Some(Err(Error::SmallSize { min: CHUNK_SIZE, ref path, .. }))
+                if path.as_deref() == Some(Path::new("test-files/small.txt"))
+        ));
+        assert!(matches!(digests.subdb, Some(Err(Error::SmallSize { .. }))));
+        assert!(matches!(
+            digests.shooter,
+            Some(Err(Error::SmallSize { min: 12288, .. }))
+        ));
+        assert_eq!(
+            digests.napi.unwrap().unwrap(),
+            NapiHash::from_path("test-files/small.txt").unwrap()
+        );
+        assert_eq!(
+            digests.ed2k.unwrap().as_hex(),
+            "ba181efa6bf8e3fbdc25c9c92fb9763e"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use crate::fingerprint::decode_hex;
+use crate::{CHUNK_SIZE, Error, FileFingerprint, pread};
+
+/// Number of leading bytes Napiprojekt computes its digest over.
+pub(crate) const NAPI_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The identifier used by Napiprojekt: the MD5 digest of the first 10 MiB of
+/// a file, along with the `f` token derived from it.
+///
+/// Files shorter than 10 MiB are hashed whole, as the reference client does,
+/// so only empty files are rejected.
+#[derive(PartialEq, Debug)]
+pub struct NapiHash(pub [u8; 16]);
+
+impl NapiHash {
+    pub fn new(digest: [u8; 16]) -> Self {
+        NapiHash(digest)
+    }
+
+    pub fn as_hex(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns the five hex digit checksum sent as the `f` parameter of
+    /// Napiprojekt requests.
+    pub fn token(&self) -> String {
+        const INDEX: [usize; 5] = [0xe, 0x3, 0x6, 0x8, 0x2];
+        const MUL: [u64; 5] = [2, 2, 5, 4, 3];
+        const ADD: [usize; 5] = [0x0, 0xd, 0x10, 0xb, 0x5];
+
+        let hex = self.as_hex();
+        let digit = |i: usize| u64::from_str_radix(&hex[i..i + 1], 16).unwrap() as usize;
+
+        (0..5)
+            .map(|i| {
+                let start = ADD[i] + digit(INDEX[i]);
+                let value =
+                    u64::from_str_radix(&hex[start..(start + 2).min(hex.len())], 16).unwrap();
+
+                format!("{:x}", value * MUL[i]).pop().unwrap()
+            })
+            .collect()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::compute_path(path)
+    }
+
+    /// Computes the hash of an already opened file without reopening it.
+    ///
+    /// The file is read with positional reads, so its cursor is not used.
+    pub fn from_file(file: &File) -> Result<Self, Error> {
+        let size = file.metadata().map_err(Error::from)?.len();
+
+        if size == 0 {
+            return Err(Error::SmallSize {
+                path: None,
+                size,
+                min: 1,
+            });
+        }
+
+        let end = size.min(NAPI_CHUNK_SIZE);
+        let mut md5 = Md5::new();
+        let mut buffer = vec![0u8; CHUNK_SIZE as usize];
+        let mut offset = 0;
+
+        while offset < end {
+            let len = (end - offset).min(CHUNK_SIZE) as usize;
+            pread::read_exact_at(file, &mut buffer[..len], offset).map_err(Error::from)?;
+            md5.update(&buffer[..len]);
+            offset += len as u64;
+        }
+
+        Ok(NapiHash::new(md5.finalize().into()))
+    }
+
+    /// Computes the hash of any seekable source from its start.
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Self, Error> {
+        reader.seek(SeekFrom::Start(0)).map_err(Error::from)?;
+
+        Self::from_stream(reader)
+    }
+
+    /// Computes the hash of the next 10 MiB or less read from a forward-only
+    /// source, starting at its current position.
+    pub fn from_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut md5 = Md5::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut reader = reader.take(NAPI_CHUNK_SIZE);
+        let mut size = 0;
+
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    md5.update(&buffer[..read]);
+                    size += read as u64;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(Error::from(err)),
+            }
+        }
+
+        if size == 0 {
+            return Err(Error::SmallSize {
+                path: None,
+                size,
+                min: 1,
+            });
+        }
+
+        Ok(NapiHash::new(md5.finalize().into()))
+    }
+}
+
+impl FileFingerprint for NapiHash {
+    const NAME: &'static str = "napi";
+    const MIN_SIZE: u64 = 1;
+
+    fn ranges(size: u64) -> Vec<Range<u64>> {
+        let prefix = 0..size.min(NAPI_CHUNK_SIZE);
+
+        vec![prefix]
+    }
+
+    fn from_ranges(_size: u64, chunks: &[&[u8]]) -> Self {
+        NapiHash::new(Md5::digest(chunks[0]).into())
+    }
+
+    fn compute_file(file: &File) -> Result<Self, Error> {
+        Self::from_file(file)
+    }
+
+    fn compute_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_stream(reader)
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        decode_hex(hex).map(NapiHash::new)
+    }
+}
+
+impl Display for NapiHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_valid_hash_and_token() {
+        let hash = NapiHash::from_path("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(hash.as_hex(), "4769470d771c91921469fcb8ecd691f8");
+        assert_eq!(hash.token(), "2044b");
+    }
+
+    #[test]
+    fn should_hash_whole_file_shorter_than_10_mib() {
+        let hash = NapiHash::from_path("test-files/small.txt").unwrap();
+
+        assert_eq!(hash.as_hex(), "90b6d5f31731fc08a143b7a06e14b112");
+        assert_eq!(hash.token(), "06a03");
+        assert_eq!(hash.to_string(), hash.as_hex());
+    }
+
+    #[test]
+    fn should_derive_token_from_last_digit() {
+        // A seventh digit of `f` makes the third step start at the last digit,
+        // where the reference implementation's slice holds only one digit.
+        assert_eq!(
+            NapiHash::new([
+                0x01, 0x23, 0x45, 0xf7, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+                0xcd, 0xef
+            ])
+            .token(),
+            "e2b08"
+        );
+    }
+
+    #[test]
+    fn should_return_small_size_error_for_empty_file() {
+        assert!(matches!(
+            NapiHash::from_stream(io::empty()),
+            Err(Error::SmallSize {
+                path: None,
+                size: 0,
+                min: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn should_hash_from_start_without_moving_file_cursor() {
+        let mut file = File::open("test-files/breakdance.avi").unwrap();
+        file.seek(SeekFrom::Start(1000)).unwrap();
+
+        let hash = NapiHash::from_file(&file).unwrap();
+        assert_eq!(hash.as_hex(), "4769470d771c91921469fcb8ecd691f8");
+        #[cfg(unix)]
+        assert_eq!(file.stream_position().unwrap(), 1000);
+
+        assert_eq!(NapiHash::from_reader(&mut file).unwrap(), hash);
+    }
+}
@@ -0,0 +1,135 @@
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::MovieHash;
+
+/// Error returned when parsing a [`MovieHash`] from a string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string did not hold 16 digits after the optional `0x` prefix.
+    Length { actual: usize },
+    /// The string held a character that is not a hex digit.
+    InvalidDigit,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Length { actual } => {
+                write!(f, "expected 16 hex digits, got {} characters", actual)
+            }
+            Self::InvalidDigit => write!(f, "invalid hex digit"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Parses the 16 hex digits printed by [`Display`], in either case and with
+/// an optional `0x` prefix.
+impl FromStr for MovieHash {
+    type Err = ParseError;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let digits = hex
+            .strip_prefix("0x")
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex);
+
+        let len = digits.chars().count();
+
+        if len != 16 {
+            return Err(ParseError::Length { actual: len });
+        }
+
+        digits
+            .chars()
+            .try_fold(0u64, |hash, digit| {
+                let value = digit.to_digit(16).ok_or(ParseError::InvalidDigit)?;
+                Ok(hash << 4 | u64::from(value))
+            })
+            .map(MovieHash)
+    }
+}
+
+impl TryFrom<&str> for MovieHash {
+    type Error = ParseError;
+
+    fn try_from(hex: &str) -> Result<Self, Self::Error> {
+        hex.parse()
+    }
+}
+
+/// Formats the 16 zero-padded digits, prefixed with `0x` by `{:#x}`.
+impl fmt::LowerHex for MovieHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Formats the 16 zero-padded digits, prefixed with `0x` by `{:#X}`.
+impl fmt::UpperHex for MovieHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_either_case_with_optional_prefix() {
+        for hex in [
+            "8e245d9679d31e12",
+            "8E245D9679D31E12",
+            "0x8e245d9679d31e12",
+            "0X8e245D9679d31E12",
+        ] {
+            assert_eq!(hex.parse(), Ok(MovieHash(0x8e245d9679d31e12)));
+        }
+
+        assert_eq!(
+            MovieHash::try_from("0000000000000abc"),
+            Ok(MovieHash(0xabc))
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_strings() {
+        let test_cases = [
+            ("", ParseError::Length { actual: 0 }),
+            ("0x", ParseError::Length { actual: 0 }),
+            ("8e245d9679d31e1", ParseError::Length { actual: 15 }),
+            ("8e245d9679d31e12a", ParseError::Length { actual: 17 }),
+            ("+e245d9679d31e12", ParseError::InvalidDigit),
+            ("8e245d9679d31e1g", ParseError::InvalidDigit),
+            ("8e245d9679d31e1é", ParseError::InvalidDigit),
+            ("8e245d9679d31e12é", ParseError::Length { actual: 17 }),
+        ];
+
+        for (hex, err) in test_cases {
+            assert_eq!(hex.parse::<MovieHash>(), Err(err), "{}", hex);
+        }
+    }
+
+    #[test]
+    fn should_format_padded_hex() {
+        let hash = MovieHash(0xabc);
+
+        assert_eq!(format!("{:x}", hash), "0000000000000abc");
+        assert_eq!(format!("{:X}", hash), "0000000000000ABC");
+        assert_eq!(format!("{:#x}", hash), "0x0000000000000abc");
+        assert_eq!(format!("{:#X}", hash).parse(), Ok(hash));
+    }
+}
@@ -0,0 +1,215 @@
+use core::convert::Infallible;
+use core::fmt::Display;
+
+use crate::{CHUNK_SIZE, MovieHash, add_words};
+
+/// Errors of the allocation-free API, which is available without `std`. `E`
+/// is the error type of the reader the hash was computed from, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartsError<E = Infallible> {
+    SmallSize {
+        size: u64,
+        min: u64,
+    },
+    /// The head, the tail or the two together held `actual` bytes instead of
+    /// `expected`.
+    Length {
+        expected: u64,
+        actual: u64,
+    },
+    UnexpectedEof,
+    Io(E),
+}
+
+impl<E: Display> Display for PartsError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::SmallSize { size, min } => {
+                write!(f, "file size of {} bytes is less than {} bytes", size, min)
+            }
+            Self::Length { expected, actual } => {
+                write!(
+                    f,
+                    "expected {} bytes of file data, got {}",
+                    expected, actual
+                )
+            }
+            Self::UnexpectedEof => write!(f, "failed to fill whole buffer"),
+            Self::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + Display> core::error::Error for PartsError<E> {}
+
+#[cfg(feature = "std")]
+impl From<PartsError> for crate::Error {
+    fn from(value: PartsError) -> Self {
+        use std::io;
+
+        match value {
+            PartsError::SmallSize { size, min } => crate::Error::SmallSize {
+                path: None,
+                size,
+                min,
+            },
+            PartsError::Length { .. } => {
+                io::Error::new(io::ErrorKind::InvalidInput, value.to_string()).into()
+            }
+            PartsError::UnexpectedEof => io::Error::from(io::ErrorKind::UnexpectedEof).into(),
+            PartsError::Io(never) => match never {},
+        }
+    }
+}
+
+/// Computes a [`MovieHash`] from the head and then the tail of a file, fed
+/// in pieces of any length, without allocating.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    hash: u64,
+    word: [u8; 8],
+    word_len: usize,
+    len: u64,
+}
+
+impl Accumulator {
+    /// Starts the hash of a file of `size` bytes.
+    pub fn new(size: u64) -> Result<Self, PartsError> {
+        if size < CHUNK_SIZE {
+            return Err(PartsError::SmallSize {
+                size,
+                min: CHUNK_SIZE,
+            });
+        }
+
+        Ok(Accumulator {
+            hash: size,
+            word: [0; 8],
+            word_len: 0,
+            len: 0,
+        })
+    }
+
+    /// Adds the next bytes of the head, or of the tail once the whole head
+    /// has been added.
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len() as u64;
+
+        if self.word_len > 0 {
+            let len = bytes.len().min(8 - self.word_len);
+            self.word[self.word_len..self.word_len + len].copy_from_slice(&bytes[..len]);
+            self.word_len += len;
+            bytes = &bytes[len..];
+
+            if self.word_len < 8 {
+                return;
+            }
+
+            self.hash = add_words(self.hash, &self.word);
+            self.word_len = 0;
+        }
+
+        self.hash = add_words(self.hash, bytes);
+
+        let rest = bytes.len() % 8;
+        self.word[..rest].copy_from_slice(&bytes[bytes.len() - rest..]);
+        self.word_len = rest;
+    }
+
+    /// Number of head and tail bytes still to be added.
+    pub fn remaining(&self) -> u64 {
+        (CHUNK_SIZE * 2).saturating_sub(self.len)
+    }
+
+    /// Returns the hash, provided exactly the head and tail were added.
+    pub fn finish(self) -> Result<MovieHash, PartsError> {
+        if self.len != CHUNK_SIZE * 2 {
+            return Err(PartsError::Length {
+                expected: CHUNK_SIZE * 2,
+                actual: self.len,
+            });
+        }
+
+        Ok(MovieHash::new(self.hash))
+    }
+}
+
+impl MovieHash {
+    /// Computes the hash of a file of `size` bytes from its first and last
+    /// [`CHUNK_SIZE`] bytes. This is the arithmetic behind every other
+    /// constructor and needs neither `std` nor an allocator.
+    pub fn from_parts(size: u64, head: &[u8], tail: &[u8]) -> Result<Self, PartsError> {
+        let mut accumulator = Accumulator::new(size)?;
+
+        for chunk in [head, tail] {
+            if chunk.len() as u64 != CHUNK_SIZE {
+                return Err(PartsError::Length {
+                    expected: CHUNK_SIZE,
+                    actual: chunk.len() as u64,
+                });
+            }
+
+            accumulator.update(chunk);
+        }
+
+        accumulator.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn should_return_same_hash_as_from_path() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+        let size = bytes.len() as u64;
+        let (head, tail) = (
+            &bytes[..CHUNK_SIZE as usize],
+            &bytes[bytes.len() - CHUNK_SIZE as usize..],
+        );
+
+        assert_eq!(
+            MovieHash::from_parts(size, head, tail).unwrap(),
+            MovieHash(0x8e245d9679d31e12)
+        );
+
+        // Pieces that split words must give the same result.
+        let mut accumulator = Accumulator::new(size).unwrap();
+        for piece in head.chunks(3).chain(tail.chunks(4099)) {
+            accumulator.update(piece);
+        }
+        assert_eq!(accumulator.remaining(), 0);
+        assert_eq!(accumulator.finish().unwrap(), MovieHash(0x8e245d9679d31e12));
+    }
+
+    #[test]
+    fn should_return_errors() {
+        let chunk = [0u8; CHUNK_SIZE as usize];
+
+        assert_eq!(
+            MovieHash::from_parts(20, &chunk, &chunk),
+            Err(PartsError::SmallSize {
+                size: 20,
+                min: CHUNK_SIZE
+            })
+        );
+        assert_eq!(
+            MovieHash::from_parts(CHUNK_SIZE, &chunk[1..], &chunk),
+            Err(PartsError::Length {
+                expected: CHUNK_SIZE,
+                actual: CHUNK_SIZE - 1
+            })
+        );
+
+        let mut accumulator = Accumulator::new(CHUNK_SIZE).unwrap();
+        accumulator.update(&chunk);
+        assert_eq!(accumulator.remaining(), CHUNK_SIZE);
+        assert_eq!(
+            accumulator.finish().unwrap_err().to_string(),
+            "expected 131072 bytes of file data, got 65536"
+        );
+    }
+}
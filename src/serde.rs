@@ -0,0 +1,150 @@
+//! Serde support for [`MovieHash`], which is serialized as its 16 hex digit
+//! string since the `u64` does not fit in a JavaScript number.
+//!
+//! Deserializing accepts both the string and the numeric form. Use
+//! [`numeric`] with `#[serde(with = "moviehash::serde::numeric")]` to
+//! serialize a field as a number instead.
+
+use core::fmt;
+
+use ::serde::de::{self, Deserializer, Visitor};
+use ::serde::{Deserialize, Serialize, Serializer};
+
+use crate::MovieHash;
+
+impl Serialize for MovieHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+        let mut hex = [0u8; 16];
+        for (i, digit) in hex.iter_mut().enumerate() {
+            *digit = DIGITS[(self.0 >> (60 - 4 * i) & 0xf) as usize];
+        }
+
+        serializer.serialize_str(core::str::from_utf8(&hex).unwrap())
+    }
+}
+
+impl<'de> Deserialize<'de> for MovieHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Binary formats do not describe their types, so only the string
+        // written by `serialize` can be read back from them.
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(MovieHashVisitor)
+        } else {
+            deserializer.deserialize_str(MovieHashVisitor)
+        }
+    }
+}
+
+struct MovieHashVisitor;
+
+impl Visitor<'_> for MovieHashVisitor {
+    type Value = MovieHash;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a 16 digit hex string or an unsigned 64-bit integer")
+    }
+
+    fn visit_str<E: de::Error>(self, hex: &str) -> Result<MovieHash, E> {
+        hex.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(hex), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, hash: u64) -> Result<MovieHash, E> {
+        Ok(MovieHash(hash))
+    }
+
+    fn visit_i64<E: de::Error>(self, hash: i64) -> Result<MovieHash, E> {
+        u64::try_from(hash)
+            .map(MovieHash)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(hash), &self))
+    }
+}
+
+/// Serializes a [`MovieHash`] as its `u64` value, for formats and consumers
+/// that expect a number. Deserializing accepts both forms from human-readable
+/// formats and the number from binary ones.
+pub mod numeric {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(hash: &MovieHash, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(hash.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<MovieHash, D::Error> {
+        if deserializer.is_human_readable() {
+            MovieHash::deserialize(deserializer)
+        } else {
+            deserializer.deserialize_u64(MovieHashVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::MovieHash;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Entry {
+        hash: MovieHash,
+        #[serde(with = "super::numeric")]
+        id: MovieHash,
+    }
+
+    #[test]
+    fn should_serialize_as_hex_or_number() {
+        let entry = Entry {
+            hash: MovieHash(0x8e245d9679d31e12),
+            id: MovieHash(0x0000000000000abc),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap(),
+            json!({ "hash": "8e245d9679d31e12", "id": 0xabc })
+        );
+    }
+
+    #[test]
+    fn should_deserialize_either_form() {
+        let expected = Entry {
+            hash: MovieHash(0x8e245d9679d31e12),
+            id: MovieHash(0x8e245d9679d31e12),
+        };
+
+        assert_eq!(
+            serde_json::from_value::<Entry>(json!({
+                "hash": 0x8e245d9679d31e12u64,
+                "id": "8E245D9679D31E12",
+            }))
+            .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_hashes() {
+        for value in [
+            json!("8e245d9679d31e1"),
+            json!("+e245d9679d31e12"),
+            json!(-1),
+            json!(1.5),
+        ] {
+            assert!(serde_json::from_value::<MovieHash>(value).is_err());
+        }
+    }
+
+    #[test]
+    fn should_round_trip_binary_format() {
+        let entry = Entry {
+            hash: MovieHash(0x8e245d9679d31e12),
+            id: MovieHash(0x0000000000000abc),
+        };
+        let bytes = postcard::to_allocvec(&entry).unwrap();
+
+        assert_eq!(postcard::from_bytes::<Entry>(&bytes).unwrap(), entry);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::ops::Range;
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use crate::fingerprint::decode_hex;
+use crate::{Error, FileFingerprint};
+
+/// Size of each of the four blocks.
+pub(crate) const SHOOTER_BLOCK_SIZE: u64 = 4096;
+
+/// The fingerprint used by Shooter and SVPlayer: the MD5 digests of four
+/// 4 KiB blocks read at 4 KiB, two thirds, one third and 8 KiB before the end
+/// of the file, in that order.
+///
+/// The block at two thirds only fits in files of at least three blocks, so
+/// smaller files are rejected.
+#[derive(PartialEq, Debug)]
+pub struct ShooterHash(pub [[u8; 16]; 4]);
+
+impl ShooterHash {
+    pub fn new(digests: [[u8; 16]; 4]) -> Self {
+        ShooterHash(digests)
+    }
+
+    /// Returns the four hex digests joined with `;`.
+    pub fn as_hex(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::compute_path(path)
+    }
+
+    /// Computes the hash of an already opened file without reopening it.
+    pub fn from_file(file: &File) -> Result<Self, Error> {
+        Self::compute_file(file)
+    }
+
+    /// Computes the hash of any seekable source, determining its size by
+    /// seeking to the end.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self, Error> {
+        Self::compute_reader(reader)
+    }
+
+    /// Computes the hash of a seekable source whose total length is already known.
+    pub fn from_reader_with_size<R: Read + Seek>(reader: R, size: u64) -> Result<Self, Error> {
+        Self::compute_reader_with_size(reader, size)
+    }
+
+    pub(crate) fn from_blocks(blocks: [&[u8]; 4]) -> Self {
+        ShooterHash::new(blocks.map(|block| Md5::digest(block).into()))
+    }
+}
+
+/// Offsets of the four blocks in a file of `size` bytes, in hashing order.
+pub(crate) fn shooter_offsets(size: u64) -> [u64; 4] {
+    [
+        SHOOTER_BLOCK_SIZE,
+        size / 3 * 2,
+        size / 3,
+        size - SHOOTER_BLOCK_SIZE * 2,
+    ]
+}
+
+impl FileFingerprint for ShooterHash {
+    const NAME: &'static str = "shooter";
+    const MIN_SIZE: u64 = SHOOTER_BLOCK_SIZE * 3;
+
+    fn ranges(size: u64) -> Vec<Range<u64>> {
+        shooter_offsets(size)
+            .map(|offset| offset..offset + SHOOTER_BLOCK_SIZE)
+            .to_vec()
+    }
+
+    fn from_ranges(_size: u64, chunks: &[&[u8]]) -> Self {
+        Self::from_blocks([chunks[0], chunks[1], chunks[2], chunks[3]])
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        let mut digests = [[0; 16]; 4];
+        let mut parts = hex.split(';');
+
+        for digest in &mut digests {
+            *digest = decode_hex(parts.next()?)?;
+        }
+
+        parts.next().is_none().then_some(ShooterHash::new(digests))
+    }
+}
+
+impl Display for ShooterHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, digest) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+
+            for byte in digest {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[test]
+    fn should_return_valid_hash() {
+        assert_eq!(
+            ShooterHash::from_path("test-files/breakdance.avi")
+                .unwrap()
+                .as_hex(),
+            "e12e2a43c22d7c050d72209b1e1d2dcf;\
+             9badd73928c32cf77af96b055b383c47;\
+             37b7a205ac45206ae04c0844c5871754;\
+             4ca1c6bfabb1e83b932d8452ba6082a6"
+        );
+    }
+
+    #[test]
+    fn should_hash_smallest_file_with_three_blocks() {
+        // Here the first, third and fourth blocks all start at 4 KiB.
+        let bytes: Vec<u8> = (0..12288).map(|i| (i / 7) as u8).collect();
+
+        assert_eq!(
+            ShooterHash::from_reader(io::Cursor::new(bytes))
+                .unwrap()
+                .as_hex(),
+            "e617b16981099fb139bd01090ab62629;\
+             44b4d9def2880072bfed524496a18fc1;\
+             e617b16981099fb139bd01090ab62629;\
+             e617b16981099fb139bd01090ab62629"
+        );
+    }
+
+    #[test]
+    fn should_return_small_size_error() {
+        let err = ShooterHash::from_reader(io::Cursor::new(vec![0u8; 12287])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SmallSize {
+                path: None,
+                size: 12287,
+                min: 12288
+            }
+        ));
+
+        let err = ShooterHash::from_path("test-files/small.txt").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "test-files/small.txt: file size of 20 bytes is less than 12288 bytes"
+        );
+    }
+}
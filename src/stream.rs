@@ -0,0 +1,115 @@
+use std::io::{self, Read};
+
+use crate::{CHUNK_SIZE, Error, MovieHash, add_words};
+
+impl MovieHash {
+    /// Computes the hash of a forward-only source such as a pipe or standard
+    /// input in a single pass.
+    ///
+    /// The head is kept as it is read and the tail in a 64 KiB ring buffer,
+    /// so memory use does not depend on the length of the stream.
+    pub fn from_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        let (size, head, tail) = read_stream_head_and_tail(reader)?;
+
+        Ok(MovieHash::new(add_words(add_words(size, &head), &tail)))
+    }
+}
+
+/// Reads `reader` to the end, returning its length along with its first and
+/// last `CHUNK_SIZE` bytes.
+pub(crate) fn read_stream_head_and_tail<R: Read>(
+    mut reader: R,
+) -> Result<(u64, Vec<u8>, Vec<u8>), Error> {
+    let chunk_size = CHUNK_SIZE as usize;
+    let mut head = Vec::with_capacity(chunk_size);
+    let mut ring = vec![0u8; chunk_size];
+    let mut buffer = vec![0u8; chunk_size];
+    let mut size: u64 = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::from(err)),
+        };
+        let data = &buffer[..read];
+
+        let missing = chunk_size - head.len();
+        head.extend_from_slice(&data[..missing.min(read)]);
+
+        // The byte at offset `o` lives at `o % CHUNK_SIZE` in the ring.
+        let start = (size % CHUNK_SIZE) as usize;
+        let first = read.min(chunk_size - start);
+        ring[start..start + first].copy_from_slice(&data[..first]);
+        ring[..read - first].copy_from_slice(&data[first..]);
+
+        size += read as u64;
+    }
+
+    if size < CHUNK_SIZE {
+        return Err(Error::SmallSize {
+            path: None,
+            size,
+            min: CHUNK_SIZE,
+        });
+    }
+
+    ring.rotate_left((size % CHUNK_SIZE) as usize);
+
+    Ok((size, head, ring))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Yields its bytes a few at a time, like a pipe.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(self.1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn should_return_same_hash_as_seekable_reader() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+
+        for step in [3, 4099, 65536, 100000] {
+            assert_eq!(
+                MovieHash::from_stream(Trickle(&bytes, step))
+                    .unwrap()
+                    .as_hex(),
+                "8e245d9679d31e12"
+            );
+        }
+
+        for len in [65536, 65539, 131072, 131077] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+
+            assert_eq!(
+                MovieHash::from_stream(Trickle(&bytes, 1000)).unwrap(),
+                MovieHash::from_reader(Cursor::new(&bytes)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn should_return_small_size_error() {
+        assert!(matches!(
+            MovieHash::from_stream(&[0u8; 100][..]),
+            Err(Error::SmallSize {
+                size: 100,
+                min: CHUNK_SIZE,
+                ..
+            })
+        ));
+    }
+}
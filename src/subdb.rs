@@ -0,0 +1,150 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::ops::Range;
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use crate::fingerprint::decode_hex;
+use crate::stream::read_stream_head_and_tail;
+use crate::{CHUNK_SIZE, Error, FileFingerprint};
+
+/// The hash used by the SubDB protocol: the MD5 digest of the first and last
+/// 64 KiB of a file, concatenated.
+#[derive(PartialEq, Debug)]
+pub struct SubDbHash(pub [u8; 16]);
+
+impl SubDbHash {
+    pub fn new(digest: [u8; 16]) -> Self {
+        SubDbHash(digest)
+    }
+
+    pub fn as_hex(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::compute_path(path)
+    }
+
+    /// Computes the hash of an already opened file without reopening it.
+    pub fn from_file(file: &File) -> Result<Self, Error> {
+        Self::compute_file(file)
+    }
+
+    /// Computes the hash of any seekable source, determining its size by
+    /// seeking to the end.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self, Error> {
+        Self::compute_reader(reader)
+    }
+
+    /// Computes the hash of a seekable source whose total length is already known.
+    pub fn from_reader_with_size<R: Read + Seek>(reader: R, size: u64) -> Result<Self, Error> {
+        Self::compute_reader_with_size(reader, size)
+    }
+
+    /// Computes the hash of a forward-only source in a single pass, see
+    /// [`crate::MovieHash::from_stream`].
+    pub fn from_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        let (_, head, tail) = read_stream_head_and_tail(reader)?;
+
+        Ok(Self::from_head_and_tail(&head, &tail))
+    }
+
+    pub(crate) fn from_head_and_tail(head: &[u8], tail: &[u8]) -> Self {
+        SubDbHash::new(
+            Md5::new()
+                .chain_update(head)
+                .chain_update(tail)
+                .finalize()
+                .into(),
+        )
+    }
+}
+
+impl FileFingerprint for SubDbHash {
+    const NAME: &'static str = "subdb";
+    const MIN_SIZE: u64 = CHUNK_SIZE;
+
+    fn ranges(size: u64) -> Vec<Range<u64>> {
+        vec![0..CHUNK_SIZE, size - CHUNK_SIZE..size]
+    }
+
+    fn from_ranges(_size: u64, chunks: &[&[u8]]) -> Self {
+        Self::from_head_and_tail(chunks[0], chunks[1])
+    }
+
+    fn compute_stream<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_stream(reader)
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        decode_hex(hex).map(SubDbHash::new)
+    }
+}
+
+impl Display for SubDbHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::CHUNK_SIZE;
+
+    #[test]
+    fn should_return_valid_hash() {
+        assert_eq!(
+            SubDbHash::from_path("test-files/breakdance.avi")
+                .unwrap()
+                .as_hex(),
+            "559075d64f311fba4abc08a43b1eff7e"
+        );
+    }
+
+    #[test]
+    fn should_return_same_hash_for_reader() {
+        let bytes = std::fs::read("test-files/breakdance.avi").unwrap();
+
+        assert_eq!(
+            SubDbHash::from_reader(io::Cursor::new(&bytes)).unwrap(),
+            SubDbHash::from_path("test-files/breakdance.avi").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_return_hash_of_file_of_exactly_one_chunk() {
+        // Head and tail are the same 64 KiB, so the digest covers them twice.
+        assert_eq!(
+            SubDbHash::from_reader(io::Cursor::new(vec![0u8; CHUNK_SIZE as usize]))
+                .unwrap()
+                .as_hex(),
+            "0dfbe8aa4c20b52e1b8bf3cb6cbdf193"
+        );
+    }
+
+    #[test]
+    fn should_return_same_errors_as_movie_hash() {
+        let err = SubDbHash::from_path("test-files/small.txt").unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::SmallSize { path: Some(path), size: 20, min: CHUNK_SIZE }
+                if path == Path::new("test-files/small.txt")
+        ));
+
+        let err = SubDbHash::from_path("test-files/non-existing.mp4").unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::Io { source, .. } if source.kind() == io::ErrorKind::NotFound
+        ));
+    }
+}
@@ -0,0 +1,17 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns an empty directory for the test `name`, unique to this process
+/// so that concurrent test runs do not clobber each other.
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("moviehash-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Returns the contents of the sample video.
+pub(crate) fn movie() -> Vec<u8> {
+    fs::read("test-files/breakdance.avi").unwrap()
+}
@@ -0,0 +1,299 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::{Error, FileFingerprint, MovieHash, VIDEO_EXTENSIONS};
+
+/// Recursively hashes the video files below `root` with the default
+/// [`HashWalker`] settings.
+pub fn hash_dir<P: AsRef<Path>>(root: P) -> Results {
+    HashWalker::new(root).walk()
+}
+
+/// Walks a directory tree and hashes the files in it on a pool of worker
+/// threads, yielding results in completion order.
+///
+/// Files are hashed with [`MovieHash`] unless another [`FileFingerprint`] is
+/// chosen with [`HashWalker::fingerprint`].
+#[derive(Debug)]
+pub struct HashWalker<F = MovieHash> {
+    root: PathBuf,
+    threads: usize,
+    extensions: Option<Vec<String>>,
+    fingerprint: PhantomData<fn() -> F>,
+}
+
+impl<F> Clone for HashWalker<F> {
+    fn clone(&self) -> Self {
+        HashWalker {
+            root: self.root.clone(),
+            threads: self.threads,
+            extensions: self.extensions.clone(),
+            fingerprint: PhantomData,
+        }
+    }
+}
+
+impl HashWalker {
+    /// Creates a walker over `root` that hashes files with one of the
+    /// [`VIDEO_EXTENSIONS`] on as many threads as the machine has cores.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        HashWalker {
+            root: root.as_ref().to_path_buf(),
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            extensions: Some(VIDEO_EXTENSIONS.iter().map(|e| e.to_string()).collect()),
+            fingerprint: PhantomData,
+        }
+    }
+}
+
+impl<F: FileFingerprint + Send + 'static> HashWalker<F> {
+    /// Hashes the files with `G` instead, keeping the other settings.
+    pub fn fingerprint<G: FileFingerprint + Send + 'static>(self) -> HashWalker<G> {
+        HashWalker {
+            root: self.root,
+            threads: self.threads,
+            extensions: self.extensions,
+            fingerprint: PhantomData,
+        }
+    }
+
+    /// Sets the number of worker threads. Network mounts usually benefit from
+    /// many more threads than cores, since each file needs only two reads.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Only hashes files with one of `extensions`, compared case-insensitively.
+    pub fn extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions = Some(extensions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Hashes every file regardless of its extension.
+    pub fn all_files(mut self) -> Self {
+        self.extensions = None;
+        self
+    }
+
+    /// Starts walking and hashing in the background.
+    ///
+    /// Directories that cannot be read are yielded with their error. Symbolic
+    /// links to files are hashed, symbolic links to directories are skipped.
+    pub fn walk(self) -> Results<F> {
+        let (path_sender, path_receiver) = mpsc::sync_channel(self.threads * 2);
+        let (result_sender, result_receiver) = mpsc::channel();
+        let path_receiver = Arc::new(Mutex::new(path_receiver));
+
+        for _ in 0..self.threads {
+            let paths = Arc::clone(&path_receiver);
+            let results = result_sender.clone();
+
+            thread::spawn(move || {
+                loop {
+                    let path: PathBuf = match paths.lock().map(|paths| paths.recv()) {
+                        Ok(Ok(path)) => path,
+                        _ => return,
+                    };
+                    let hash = F::compute_path(&path);
+
+                    if results.send((path, hash)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+
+        thread::spawn(move || {
+            let _ = self.visit(&self.root, &path_sender, &result_sender);
+        });
+
+        Results {
+            receiver: result_receiver,
+        }
+    }
+
+    /// Sends every matching file below `dir` to the workers, stopping early
+    /// once the consumer has gone away.
+    fn visit(
+        &self,
+        dir: &Path,
+        paths: &SyncSender<PathBuf>,
+        results: &mpsc::Sender<(PathBuf, Result<F, Error>)>,
+    ) -> Result<(), ()> {
+        let entries = match read_dir_sorted(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                return results
+                    .send((dir.to_path_buf(), Err(err.with_path(dir))))
+                    .map_err(drop);
+            }
+        };
+
+        for (path, file_type) in entries {
+            if file_type.is_dir() {
+                self.visit(&path, paths, results)?;
+            } else if (file_type.is_file() || path.is_file()) && self.matches(&path) {
+                paths.send(path).map_err(drop)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let Some(extensions) = &self.extensions else {
+            return true;
+        };
+
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extensions
+                    .iter()
+                    .any(|allowed| extension.eq_ignore_ascii_case(allowed))
+            })
+    }
+}
+
+impl<F: FileFingerprint + Send + 'static> IntoIterator for HashWalker<F> {
+    type Item = (PathBuf, Result<F, Error>);
+    type IntoIter = Results<F>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.walk()
+    }
+}
+
+fn read_dir_sorted(dir: &Path) -> Result<Vec<(PathBuf, fs::FileType)>, Error> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.and_then(|entry| Ok((entry.path(), entry.file_type()?))))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(entries)
+}
+
+/// Iterator over the results of a [`HashWalker`]. Dropping it stops the
+/// walk and the workers.
+#[derive(Debug)]
+pub struct Results<F = MovieHash> {
+    receiver: Receiver<(PathBuf, Result<F, Error>)>,
+}
+
+impl<F> Iterator for Results<F> {
+    type Item = (PathBuf, Result<F, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn library() -> PathBuf {
+        let root = temp_dir("walker");
+
+        for dir in ["a/b", "c", "d"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+
+        for (i, file) in ["a/one.avi", "a/b/two.MKV", "c/three.mp4", "d/four.avi"]
+            .iter()
+            .enumerate()
+        {
+            fs::write(root.join(file), vec![i as u8; 70000]).unwrap();
+        }
+
+        fs::write(root.join("c/notes.txt"), "not a video").unwrap();
+        fs::write(root.join("d/tiny.avi"), "too small").unwrap();
+
+        root
+    }
+
+    fn collect<F>(walker: HashWalker<F>) -> Vec<(PathBuf, Result<F, Error>)>
+    where
+        F: FileFingerprint + Send + 'static,
+    {
+        let mut results: Vec<_> = walker.into_iter().collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        results
+    }
+
+    #[test]
+    fn should_hash_video_files_in_parallel() {
+        let root = library();
+        let results = collect(HashWalker::new(&root).threads(3));
+        let paths: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+
+        assert_eq!(
+            paths,
+            [
+                "a/b/two.MKV",
+                "a/one.avi",
+                "c/three.mp4",
+                "d/four.avi",
+                "d/tiny.avi"
+            ]
+            .map(|file| root.join(file))
+        );
+
+        for (path, result) in results {
+            match path.file_name().unwrap().to_str().unwrap() {
+                "tiny.avi" => assert!(matches!(result, Err(Error::SmallSize { .. }))),
+                _ => assert_eq!(result.unwrap(), MovieHash::from_path(&path).unwrap()),
+            }
+        }
+    }
+
+    #[test]
+    fn should_filter_by_extension() {
+        let root = std::env::current_dir().unwrap().join("test-files");
+
+        assert_eq!(
+            collect(HashWalker::new(&root).threads(1).extensions(["txt"]))
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            [root.join("small.txt")]
+        );
+        assert_eq!(collect(HashWalker::new(&root).all_files()).len(), 2);
+        assert_eq!(
+            hash_dir(&root).next().unwrap().1.unwrap().as_hex(),
+            "8e245d9679d31e12"
+        );
+    }
+
+    #[test]
+    fn should_hash_with_other_fingerprint() {
+        let root = std::env::current_dir().unwrap().join("test-files");
+        let results = collect(HashWalker::new(&root).fingerprint::<crate::SubDbHash>());
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].1.as_ref().unwrap().as_hex(),
+            "559075d64f311fba4abc08a43b1eff7e"
+        );
+    }
+
+    #[test]
+    fn should_yield_error_for_unreadable_directory() {
+        let root = Path::new("test-files/non-existing");
+        let results = collect(HashWalker::new(root));
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, root);
+        assert!(matches!(&results[0].1, Err(Error::Io { .. })));
+    }
+}
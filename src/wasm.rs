@@ -0,0 +1,89 @@
+use wasm_bindgen::prelude::*;
+
+use crate::MovieHash;
+
+/// Largest integer a JavaScript number holds exactly, and so the largest
+/// `Blob.size`.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Computes the OpenSubtitles hash of a `Blob` of `size` bytes from the
+/// contents of `blob.slice(0, 65536)` and `blob.slice(size - 65536)`,
+/// returning the same 16 hex digits as [`MovieHash::as_hex`].
+///
+/// Exported to JavaScript when built for `wasm32-unknown-unknown` with the
+/// `wasm` feature and a `cdylib` crate type.
+#[wasm_bindgen(js_name = movieHashFromParts)]
+pub fn movie_hash_from_parts(size: f64, head: &[u8], tail: &[u8]) -> Result<String, JsError> {
+    hash_parts(size, head, tail).map_err(|message| JsError::new(&message))
+}
+
+fn hash_parts(size: f64, head: &[u8], tail: &[u8]) -> Result<String, String> {
+    if !(0.0..=MAX_SAFE_INTEGER).contains(&size) || size.fract() != 0.0 {
+        return Err(format!("invalid file size {}", size));
+    }
+
+    MovieHash::from_parts(size as u64, head, tail)
+        .map(|hash| hash.as_hex())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::CHUNK_SIZE;
+
+    /// Splits `bytes` the way the uploader slices the `Blob`.
+    fn slices(bytes: &[u8]) -> (&[u8], &[u8]) {
+        let chunk = CHUNK_SIZE as usize;
+
+        (&bytes[..chunk], &bytes[bytes.len() - chunk..])
+    }
+
+    #[test]
+    fn should_match_native_implementation() {
+        let movie = std::fs::read("test-files/breakdance.avi").unwrap();
+        let (head, tail) = slices(&movie);
+
+        assert_eq!(
+            movie_hash_from_parts(movie.len() as f64, head, tail).unwrap(),
+            MovieHash::from_path("test-files/breakdance.avi")
+                .unwrap()
+                .as_hex()
+        );
+
+        for len in [65536, 65543, 200003] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 7 % 253) as u8).collect();
+            let (head, tail) = slices(&bytes);
+
+            assert_eq!(
+                movie_hash_from_parts(len as f64, head, tail).unwrap(),
+                MovieHash::from_reader(Cursor::new(&bytes))
+                    .unwrap()
+                    .as_hex()
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_input() {
+        let chunk = [0u8; CHUNK_SIZE as usize];
+
+        assert_eq!(
+            hash_parts(1.5e6, &chunk, &chunk[1..]),
+            Err("expected 65536 bytes of file data, got 65535".to_string())
+        );
+        assert_eq!(
+            hash_parts(20.0, &chunk, &chunk),
+            Err("file size of 20 bytes is less than 65536 bytes".to_string())
+        );
+
+        for size in [-1.0, 0.5, f64::NAN, 1e300] {
+            assert_eq!(
+                hash_parts(size, &chunk, &chunk),
+                Err(format!("invalid file size {}", size))
+            );
+        }
+    }
+}